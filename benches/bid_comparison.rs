@@ -0,0 +1,85 @@
+use bidding_heap::models::v1::bid::Bid;
+use chrono::{DateTime, Duration, Utc};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bids() -> Vec<Bid> {
+    let made_at = DateTime::<Utc>::from_timestamp(1000, 100).unwrap();
+    let mut bids = Vec::new();
+    for (id, &(amount, later, removed)) in [
+        (0, false, false),
+        (0, true, false),
+        (1, false, false),
+        (1, true, false),
+        (0, false, true),
+        (0, true, true),
+        (1, false, true),
+        (1, true, true),
+    ]
+    .iter()
+    .enumerate()
+    {
+        bids.push(Bid {
+            auction_id: String::from("auction_id"),
+            bidder_id: id.to_string(),
+            id: id as i32,
+            amount,
+            made_at: made_at + Duration::days(later as i64),
+            removed_at: if removed { Some(made_at) } else { None },
+            removal_reason: None,
+        });
+    }
+    bids
+}
+
+// is_higher_bid_than as it was written before the predicates derived from cmp_bids, evaluating
+// activity, amount and time separately for the lower and the equivalent check
+fn legacy_is_higher_bid_than(bid: &Bid, other: &Bid) -> bool {
+    let is_lower = {
+        let both_bids_active = bid.is_active() && other.is_active();
+        let neither_bid_active = !(bid.is_active() || other.is_active());
+        if both_bids_active || neither_bid_active {
+            bid.amount < other.amount || (bid.amount == other.amount && other.made_at < bid.made_at)
+        } else {
+            !bid.is_active()
+        }
+    };
+    let is_equivalent = {
+        let both_bids_active = bid.is_active() && other.is_active();
+        let neither_bid_active = !(bid.is_active() || other.is_active());
+        if both_bids_active || neither_bid_active {
+            bid.amount == other.amount && other.made_at == bid.made_at
+        } else {
+            false
+        }
+    };
+    !is_lower && !is_equivalent
+}
+
+fn bid_comparison(c: &mut Criterion) {
+    let bids = bids();
+    let mut group = c.benchmark_group("is_higher_bid_than_all_pairs");
+
+    group.bench_function("legacy", |b| {
+        b.iter(|| {
+            for bid in &bids {
+                for other in &bids {
+                    black_box(legacy_is_higher_bid_than(black_box(bid), black_box(other)));
+                }
+            }
+        })
+    });
+    group.bench_function("cmp_bids", |b| {
+        b.iter(|| {
+            for bid in &bids {
+                for other in &bids {
+                    black_box(black_box(bid).is_higher_bid_than(black_box(other)));
+                }
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bid_comparison);
+criterion_main!(benches);
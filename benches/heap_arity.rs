@@ -0,0 +1,53 @@
+use bidding_heap::models::v1::bid::Bid;
+use bidding_heap::models::v1::bidding_heap::BiddingHeap;
+use chrono::{DateTime, Duration, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+fn bids(count: i32) -> Vec<Bid> {
+    let made_at = DateTime::<Utc>::from_timestamp(1000, 100).unwrap();
+    (0..count)
+        .map(|id| Bid {
+            auction_id: String::from("auction_id"),
+            bidder_id: id.to_string(),
+            id,
+            // a fixed permutation keeps runs comparable without a random number generator
+            amount: (id * 7919) % count,
+            made_at: made_at + Duration::seconds(id as i64),
+            removed_at: None,
+            removal_reason: None,
+        })
+        .collect()
+}
+
+fn push_and_drain<const D: usize>(bids: Vec<Bid>) -> Vec<Bid> {
+    let mut heap = BiddingHeap::<D>::with_arity();
+    for bid in bids {
+        heap.push(bid);
+    }
+    heap.drain_sorted()
+}
+
+fn heap_arity(c: &mut Criterion) {
+    let bids = bids(10_000);
+    let mut group = c.benchmark_group("push_and_drain_10000");
+
+    group.bench_function("binary", |b| {
+        b.iter_batched(
+            || bids.clone(),
+            |bids| black_box(push_and_drain::<2>(bids)),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("quaternary", |b| {
+        b.iter_batched(
+            || bids.clone(),
+            |bids| black_box(push_and_drain::<4>(bids)),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, heap_arity);
+criterion_main!(benches);
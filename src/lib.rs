@@ -2,7 +2,30 @@
 extern crate serde_derive;
 
 pub mod models {
+    pub mod bid_envelope;
     pub mod v1 {
+        pub mod auction;
+        pub mod auction_result;
+        pub mod bid;
+        pub mod bid_columns;
+        pub mod bid_error;
+        pub mod bid_event;
+        pub mod bid_id_allocator;
+        pub mod bid_outcome;
+        pub mod bidding_heap;
+        pub mod comparison_explanation;
+        pub mod currency;
+        #[cfg(feature = "decimal")]
+        pub mod decimal_bid;
+        pub mod frozen_bid;
+        pub mod heap_snapshot;
+        pub mod identity_bid;
+        #[cfg(feature = "protobuf")]
+        pub mod proto;
+        pub mod removal_reason;
+        pub mod tie_break;
+    }
+    pub mod v2 {
         pub mod bid;
     }
 }
@@ -0,0 +1,76 @@
+use crate::models::{v1, v2};
+
+// wraps a bid with the version of the model it was written with
+// - the tag is flattened into the bid's own object, e.g. {"version":"v1","auction_id":...}
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "version")]
+pub enum BidEnvelope {
+    #[serde(rename = "v1")]
+    V1(v1::bid::Bid),
+    #[serde(rename = "v2")]
+    V2(v2::bid::Bid),
+}
+
+impl From<v1::bid::Bid> for BidEnvelope {
+    fn from(bid: v1::bid::Bid) -> Self {
+        BidEnvelope::V1(bid)
+    }
+}
+
+impl From<v2::bid::Bid> for BidEnvelope {
+    fn from(bid: v2::bid::Bid) -> Self {
+        BidEnvelope::V2(bid)
+    }
+}
+
+#[cfg(test)]
+mod serialization_and_deserialization {
+    use crate::models::bid_envelope::BidEnvelope;
+    use crate::models::v1::bid::Bid;
+    use crate::models::v2;
+    use chrono::DateTime;
+
+    fn bid() -> Bid {
+        let mut bid = Bid::new(
+            String::from("auction_id"),
+            String::from("bidder_id"),
+            0,
+            100,
+        );
+        bid.made_at = DateTime::from_timestamp(1000, 100).unwrap();
+        bid
+    }
+
+    #[test]
+    fn v1_round_trip_works() {
+        let envelope = BidEnvelope::from(bid());
+
+        let data = serde_json::to_string(&envelope).unwrap();
+        assert!(data.starts_with("{\"version\":\"v1\","));
+
+        let deserialized = serde_json::from_str::<BidEnvelope>(&data).unwrap();
+        assert_eq!(BidEnvelope::V1(bid()), deserialized);
+    }
+
+    #[test]
+    fn v2_round_trip_works() {
+        let envelope = BidEnvelope::from(v2::bid::Bid::from_v1(bid(), 2));
+
+        let data = serde_json::to_string(&envelope).unwrap();
+        assert!(data.starts_with("{\"version\":\"v2\","));
+        assert_eq!(
+            envelope,
+            serde_json::from_str::<BidEnvelope>(&data).unwrap()
+        );
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let data = serde_json::to_string(&BidEnvelope::from(bid()))
+            .unwrap()
+            .replacen("\"v1\"", "\"v9\"", 1);
+
+        assert!(serde_json::from_str::<BidEnvelope>(&data).is_err());
+        assert!(serde_json::from_str::<BidEnvelope>("{\"auction_id\":\"auction_id\"}").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use crate::models::v1::auction_result::AuctionResult;
+use crate::models::v1::bid::Bid;
+use crate::models::v1::bid_error::BidError;
+use crate::models::v1::bidding_heap::BiddingHeap;
+use chrono::{DateTime, Duration, Utc};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtensionPolicy {
+    // a bid made less than threshold before close pushes closes_at back by extension
+    pub threshold: Duration,
+    pub extension: Duration,
+}
+
+#[derive(Debug)]
+pub struct Auction {
+    pub id: String,
+    pub increment: i32,
+    pub closes_at: Option<DateTime<Utc>>,
+    pub extension_policy: Option<ExtensionPolicy>,
+    paused: bool,
+    bids: BiddingHeap,
+}
+
+impl Auction {
+    // a single auction's bids plus the rules a new bid must pass before it is accepted
+    // - the first bid only has to be active; later bids must beat the leader by increment
+    // - a negative increment is rejected, since it would let bids undercut the leader
+    // - with closes_at set, bids made after it are rejected, whether or not an extension applies
+    // - with both closes_at and extension_policy set, accepted late bids push the close back
+
+    pub fn new(id: String, increment: i32) -> Self {
+        Auction {
+            id,
+            increment,
+            closes_at: None,
+            extension_policy: None,
+            paused: false,
+            bids: BiddingHeap::new(),
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn bids(&self) -> &BiddingHeap {
+        &self.bids
+    }
+
+    pub fn leader(&self) -> Option<&Bid> {
+        self.bids.leader(&self.id)
+    }
+
+    pub fn place_bid(&mut self, bid: Bid) -> Result<(), BidError> {
+        if self.paused {
+            return Err(BidError::AuctionPaused);
+        }
+        if bid.auction_id != self.id {
+            return Err(BidError::AuctionMismatch);
+        }
+        if self.increment < 0 {
+            return Err(BidError::InvalidIncrement);
+        }
+        if matches!(self.closes_at, Some(closes_at) if bid.made_at > closes_at) {
+            return Err(BidError::AuctionClosed);
+        }
+        match self.leader() {
+            Some(leader) => bid.can_outbid(leader, self.increment)?,
+            None if !bid.is_active() => return Err(BidError::BidInactive),
+            None => {}
+        }
+
+        self.extend_for(&bid);
+        self.bids.push(bid);
+        Ok(())
+    }
+
+    pub fn finalize(self) -> AuctionResult {
+        AuctionResult::seal(
+            self.id.clone(),
+            self.leader().cloned(),
+            self.bids.clearing_price(&self.id, 1),
+            self.bids.len(),
+        )
+    }
+
+    fn extend_for(&mut self, bid: &Bid) {
+        if let (Some(closes_at), Some(policy)) = (self.closes_at, self.extension_policy) {
+            let until_close = closes_at - bid.made_at;
+            if until_close > Duration::zero() && until_close < policy.threshold {
+                self.closes_at = Some(closes_at + policy.extension);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1::auction::{Auction, ExtensionPolicy};
+    use crate::models::v1::auction_result::AuctionResult;
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bid_error::BidError;
+    use chrono::{DateTime, Duration, Utc};
+
+    fn bid(id: i32, amount: i32) -> Bid {
+        Bid::new(
+            String::from("auction_id"),
+            String::from("bidder_id"),
+            id,
+            amount,
+        )
+    }
+
+    #[test]
+    fn place_bid_accepts_first_bid() {
+        let mut auction = Auction::new(String::from("auction_id"), 5);
+
+        assert_eq!(Ok(()), auction.place_bid(bid(0, 1)));
+        assert_eq!(0, auction.leader().unwrap().id);
+    }
+
+    #[test]
+    fn place_bid_rejects_bid_below_increment() {
+        let mut auction = Auction::new(String::from("auction_id"), 5);
+        auction.place_bid(bid(0, 100)).unwrap();
+
+        assert_eq!(
+            Err(BidError::BelowMinimumIncrement),
+            auction.place_bid(bid(1, 104))
+        );
+        assert_eq!(1, auction.bids().len());
+    }
+
+    #[test]
+    fn place_bid_accepts_bid_at_increment() {
+        let mut auction = Auction::new(String::from("auction_id"), 5);
+        auction.place_bid(bid(0, 100)).unwrap();
+
+        assert_eq!(Ok(()), auction.place_bid(bid(1, 105)));
+        assert_eq!(1, auction.leader().unwrap().id);
+    }
+
+    #[test]
+    fn place_bid_rejects_negative_increment() {
+        let mut auction = Auction::new(String::from("auction_id"), -5);
+
+        assert_eq!(
+            Err(BidError::InvalidIncrement),
+            auction.place_bid(bid(0, 100))
+        );
+        assert!(auction.bids().is_empty());
+
+        auction.increment = 0;
+        auction.place_bid(bid(0, 100)).unwrap();
+        auction.increment = -5;
+        assert_eq!(
+            Err(BidError::InvalidIncrement),
+            auction.place_bid(bid(1, 96))
+        );
+        assert_eq!(1, auction.bids().len());
+    }
+
+    #[test]
+    fn place_bid_rejects_other_auctions() {
+        let mut auction = Auction::new(String::from("other_auction_id"), 5);
+
+        assert_eq!(
+            Err(BidError::AuctionMismatch),
+            auction.place_bid(bid(0, 100))
+        );
+        assert!(auction.bids().is_empty());
+    }
+
+    fn extending_auction(closes_at: DateTime<Utc>) -> Auction {
+        let mut auction = Auction::new(String::from("auction_id"), 5);
+        auction.closes_at = Some(closes_at);
+        auction.extension_policy = Some(ExtensionPolicy {
+            threshold: Duration::minutes(2),
+            extension: Duration::minutes(5),
+        });
+        auction
+    }
+
+    #[test]
+    fn place_bid_extends_close_for_late_bid() {
+        let closes_at = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let mut auction = extending_auction(closes_at);
+        let mut late_bid = bid(0, 100);
+        late_bid.made_at = closes_at - Duration::seconds(30);
+
+        auction.place_bid(late_bid).unwrap();
+        assert_eq!(Some(closes_at + Duration::minutes(5)), auction.closes_at);
+    }
+
+    #[test]
+    fn place_bid_keeps_close_for_early_bid() {
+        let closes_at = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let mut auction = extending_auction(closes_at);
+        let mut early_bid = bid(0, 100);
+        early_bid.made_at = closes_at - Duration::minutes(10);
+
+        auction.place_bid(early_bid).unwrap();
+        assert_eq!(Some(closes_at), auction.closes_at);
+    }
+
+    #[test]
+    fn place_bid_rejects_bid_after_close() {
+        let closes_at = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let mut auction = extending_auction(closes_at);
+        let mut after_close = bid(0, 100);
+        after_close.made_at = closes_at + Duration::seconds(1);
+
+        assert_eq!(Err(BidError::AuctionClosed), auction.place_bid(after_close));
+        assert_eq!(Some(closes_at), auction.closes_at);
+        assert!(auction.bids().is_empty());
+
+        let mut at_close = bid(1, 100);
+        at_close.made_at = closes_at;
+        auction.place_bid(at_close).unwrap();
+        assert_eq!(Some(closes_at), auction.closes_at);
+    }
+
+    #[test]
+    fn place_bid_does_not_extend_for_rejected_bid() {
+        let closes_at = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let mut auction = extending_auction(closes_at);
+        let mut first_bid = bid(0, 100);
+        first_bid.made_at = closes_at - Duration::minutes(10);
+        auction.place_bid(first_bid).unwrap();
+        let mut low_bid = bid(1, 101);
+        low_bid.made_at = closes_at - Duration::seconds(30);
+
+        assert!(auction.place_bid(low_bid).is_err());
+        assert_eq!(Some(closes_at), auction.closes_at);
+    }
+
+    #[test]
+    fn finalize_works() {
+        let mut auction = Auction::new(String::from("auction_id"), 5);
+        auction.place_bid(bid(0, 100)).unwrap();
+        auction.place_bid(bid(1, 110)).unwrap();
+        auction.place_bid(bid(2, 130)).unwrap();
+        assert!(auction.place_bid(bid(3, 131)).is_err());
+
+        let result = auction.finalize();
+        assert_eq!("auction_id", result.auction_id);
+        assert_eq!(Some(2), result.winner.as_ref().map(|winner| winner.id));
+        assert_eq!(Some(130), result.clearing_price);
+        assert_eq!(3, result.total_bids);
+        assert!(result.is_intact());
+
+        let serialized = serde_json::to_string(&result).unwrap();
+        let mut corrupted = serde_json::from_str::<AuctionResult>(&serialized).unwrap();
+        assert_eq!(result, corrupted);
+        corrupted.clearing_price = Some(100);
+        assert!(!corrupted.is_intact());
+    }
+
+    #[test]
+    fn finalize_works_without_bids() {
+        let result = Auction::new(String::from("auction_id"), 5).finalize();
+
+        assert_eq!(None, result.winner);
+        assert_eq!(None, result.clearing_price);
+        assert_eq!(0, result.total_bids);
+        assert!(result.is_intact());
+    }
+
+    #[test]
+    fn place_bid_rejects_bids_while_paused() {
+        let mut auction = Auction::new(String::from("auction_id"), 5);
+        auction.pause();
+
+        assert!(auction.is_paused());
+        assert_eq!(Err(BidError::AuctionPaused), auction.place_bid(bid(0, 100)));
+        assert!(auction.bids().is_empty());
+    }
+
+    #[test]
+    fn place_bid_accepts_bids_after_resume() {
+        let mut auction = Auction::new(String::from("auction_id"), 5);
+        auction.pause();
+        auction.resume();
+
+        assert!(!auction.is_paused());
+        assert_eq!(Ok(()), auction.place_bid(bid(0, 100)));
+        assert_eq!(0, auction.leader().unwrap().id);
+    }
+}
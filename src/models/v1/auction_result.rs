@@ -0,0 +1,106 @@
+use crate::models::v1::bid::{fnv1a_64, Bid};
+#[cfg(feature = "crypto")]
+use hmac::Mac;
+
+// the sealed outcome of a finalized auction, detached from its heap
+// - content_hash is an unkeyed checksum over every other field: is_intact catches accidental
+//   corruption, but anyone editing the result can recompute it
+// - with the crypto feature, sign and verify give a keyed HMAC-SHA256 seal that does detect edits
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuctionResult {
+    pub auction_id: String,
+    pub winner: Option<Bid>,
+    pub clearing_price: Option<i32>,
+    pub total_bids: usize,
+    pub content_hash: u64,
+}
+
+impl AuctionResult {
+    pub fn seal(
+        auction_id: String,
+        winner: Option<Bid>,
+        clearing_price: Option<i32>,
+        total_bids: usize,
+    ) -> Self {
+        let mut result = AuctionResult {
+            auction_id,
+            winner,
+            clearing_price,
+            total_bids,
+            content_hash: 0,
+        };
+        result.content_hash = fnv1a_64(&result.canonical_bytes());
+        result
+    }
+
+    pub fn is_intact(&self) -> bool {
+        self.content_hash == fnv1a_64(&self.canonical_bytes())
+    }
+
+    #[cfg(feature = "crypto")]
+    pub fn sign(&self, key: &[u8]) -> String {
+        hex::encode(self.mac(key).finalize().into_bytes())
+    }
+
+    #[cfg(feature = "crypto")]
+    pub fn verify(&self, key: &[u8], sig: &str) -> bool {
+        match hex::decode(sig) {
+            Ok(sig) => self.mac(key).verify_slice(&sig).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(feature = "crypto")]
+    fn mac(&self, key: &[u8]) -> hmac::Hmac<sha2::Sha256> {
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&self.canonical_bytes());
+        mac
+    }
+
+    // every field except content_hash, encoded as in Bid::canonical_bytes
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.auction_id.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(self.auction_id.as_bytes());
+        match &self.winner {
+            Some(winner) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&winner.canonical_bytes());
+            }
+            None => bytes.push(0),
+        }
+        match self.clearing_price {
+            Some(clearing_price) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&clearing_price.to_be_bytes());
+            }
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&(self.total_bids as u64).to_be_bytes());
+        bytes
+    }
+}
+
+#[cfg(all(test, feature = "crypto"))]
+mod methods {
+    use crate::models::v1::auction_result::AuctionResult;
+
+    #[test]
+    fn sign_and_verify_work() {
+        let key = b"key";
+        let mut result = AuctionResult::seal(String::from("auction_id"), None, Some(130), 3);
+        let sig = result.sign(key);
+
+        assert!(result.verify(key, &sig));
+        assert!(!result.verify(b"other_key", &sig));
+        assert!(!result.verify(key, "not hex"));
+
+        // recomputing content_hash after an edit fools is_intact, but not the keyed seal
+        result.clearing_price = Some(100);
+        result.content_hash =
+            AuctionResult::seal(String::from("auction_id"), None, Some(100), 3).content_hash;
+        assert!(result.is_intact());
+        assert!(!result.verify(key, &sig));
+    }
+}
@@ -1,6 +1,24 @@
+use crate::models::v1::bid_error::BidError;
+use crate::models::v1::bid_outcome::BidOutcome;
+use crate::models::v1::comparison_explanation::{ComparisonExplanation, ComparisonRule};
+use crate::models::v1::frozen_bid::FrozenBid;
+use crate::models::v1::removal_reason::RemovalReason;
 use chrono::Utc;
+#[cfg(feature = "crypto")]
+use hmac::Mac;
+use std::cmp::Ordering;
 
-#[derive(Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
+// bids made within this many seconds of close are penalized by is_lower_bid_than_antisnipe
+pub const ANTISNIPE_WINDOW_SECONDS: i64 = 60;
+
+// 64-bit FNV-1a, kept here rather than using DefaultHasher so hashes are stable across releases
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Bid {
     pub auction_id: String,
     pub bidder_id: String,
@@ -8,11 +26,14 @@ pub struct Bid {
     pub amount: i32,
     pub made_at: chrono::DateTime<chrono::Utc>,
     pub removed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub removal_reason: Option<RemovalReason>,
 }
 
 impl Bid {
     // note on why not implementing comp methods via PartialOrd
     // - avoiding potential user confusion with equality and identity
+    // - PartialOrd is still hand-written below so that operators agree with these methods
 
     pub fn new(auction_id: String, bidder_id: String, id: i32, amount: i32) -> Self {
         Bid {
@@ -22,9 +43,63 @@ impl Bid {
             amount,
             made_at: Utc::now(),
             removed_at: None,
+            removal_reason: None,
         }
     }
 
+    pub fn from_json(s: &str) -> Result<Bid, BidError> {
+        serde_json::from_str::<Bid>(s).map_err(|e| BidError::InvalidJson(e.to_string()))
+    }
+
+    pub fn from_json_strict(s: &str) -> Result<Bid, BidError> {
+        serde_json::from_str::<StrictBid>(s)
+            .map(Bid::from)
+            .map_err(|e| BidError::InvalidJson(e.to_string()))
+    }
+
+    // serde_json::Value keeps object keys in a BTreeMap, so going through it sorts them
+    pub fn to_json_deterministic(&self) -> Result<String, BidError> {
+        serde_json::to_value(self)
+            .and_then(|value| serde_json::to_string(&value))
+            .map_err(|e| BidError::Serialization(e.to_string()))
+    }
+
+    #[cfg(feature = "bincode")]
+    pub fn to_bincode(&self) -> Result<Vec<u8>, BidError> {
+        bincode::serialize(self).map_err(|e| BidError::Serialization(e.to_string()))
+    }
+
+    #[cfg(feature = "bincode")]
+    pub fn from_bincode(bytes: &[u8]) -> Result<Bid, BidError> {
+        bincode::deserialize::<Bid>(bytes).map_err(|e| BidError::InvalidBincode(e.to_string()))
+    }
+
+    pub fn normalized(&self) -> Bid {
+        Bid {
+            auction_id: self.auction_id.trim().to_string(),
+            bidder_id: self.bidder_id.trim().to_string(),
+            ..self.clone()
+        }
+    }
+
+    pub fn normalized_strict(&self) -> Result<Bid, BidError> {
+        let bid = self.normalized();
+        if bid.auction_id.is_empty() || bid.bidder_id.is_empty() {
+            return Err(BidError::EmptyId);
+        }
+        Ok(bid)
+    }
+
+    pub fn validate(&self) -> Result<(), BidError> {
+        if self.auction_id.is_empty() || self.bidder_id.is_empty() {
+            return Err(BidError::EmptyId);
+        }
+        if self.made_at > Utc::now() {
+            return Err(BidError::FutureDated);
+        }
+        Ok(())
+    }
+
     pub fn is_active(&self) -> bool {
         self.removed_at.is_none()
     }
@@ -33,43 +108,280 @@ impl Bid {
         self.removed_at = Some(Utc::now());
     }
 
-    pub fn is_lower_bid_than(&self, other: &Self) -> bool {
-        let both_bids_active = self.is_active() && other.is_active();
-        let neither_bid_active = !(self.is_active() || other.is_active());
-        let is_lower_amount = self.amount < other.amount;
-        let is_equal_amount = self.amount == other.amount;
-        let is_later_bid = other.made_at < self.made_at;
+    pub fn remove_with_reason(&mut self, reason: RemovalReason) {
+        self.remove();
+        self.removal_reason = Some(reason);
+    }
 
-        if both_bids_active || neither_bid_active {
-            is_lower_amount || (is_equal_amount && is_later_bid)
-        } else {
-            !self.is_active()
+    pub fn update_amount(&mut self, amount: i32) -> Result<(), BidError> {
+        if !self.is_active() {
+            return Err(BidError::BidInactive);
         }
+        self.amount = amount;
+        Ok(())
+    }
+
+    pub fn freeze(self) -> FrozenBid {
+        FrozenBid::new(self)
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    pub fn made_at_in(&self, tz: chrono_tz::Tz) -> chrono::DateTime<chrono_tz::Tz> {
+        self.made_at.with_timezone(&tz)
+    }
+
+    #[inline]
+    pub fn is_lower_bid_than(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Less
     }
 
+    // an active and a removed bid are never equivalent, even with equal amounts and times
+    // - activity always decides their order, as in is_lower_bid_than
+    #[inline]
     pub fn is_equivalent_bid_to(&self, other: &Self) -> bool {
-        let both_bids_active = self.is_active() && other.is_active();
-        let neither_bid_active = !(self.is_active() || other.is_active());
+        self.cmp_bids(other) == Ordering::Equal
+    }
+
+    // like is_equivalent_bid_to, but made_at values up to tolerance apart count as simultaneous
+    pub fn is_equivalent_bid_to_within(&self, other: &Self, tolerance: chrono::Duration) -> bool {
+        let same_activity = self.is_active() == other.is_active();
         let is_equal_amount = self.amount == other.amount;
-        let is_simultaneous_bid = other.made_at == self.made_at;
+        let is_within_tolerance = (self.made_at - other.made_at).abs() <= tolerance;
 
-        if both_bids_active || neither_bid_active {
-            is_equal_amount && is_simultaneous_bid
+        same_activity && is_equal_amount && is_within_tolerance
+    }
+
+    #[inline]
+    pub fn is_higher_bid_than(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Greater
+    }
+
+    // follows the same rules, in the same order, as cmp_bids
+    pub fn explain_comparison(&self, other: &Bid) -> ComparisonExplanation {
+        let rule = if self.is_active() != other.is_active() {
+            ComparisonRule::ActivityOverride
+        } else if self.amount != other.amount {
+            ComparisonRule::AmountDifference { difference: i64::from(self.amount) - i64::from(other.amount) }
+        } else if self.made_at != other.made_at {
+            ComparisonRule::TimeTieBreak
         } else {
-            false
+            ComparisonRule::Equivalent
+        };
+
+        ComparisonExplanation { ordering: self.cmp_bids(other), rule }
+    }
+
+    #[cfg(feature = "crypto")]
+    pub fn sign(&self, key: &[u8]) -> String {
+        hex::encode(self.mac(key).finalize().into_bytes())
+    }
+
+    #[cfg(feature = "crypto")]
+    pub fn verify(&self, key: &[u8], sig: &str) -> bool {
+        match hex::decode(sig) {
+            Ok(sig) => self.mac(key).verify_slice(&sig).is_ok(),
+            Err(_) => false,
         }
     }
 
-    pub fn is_higher_bid_than(&self, other: &Self) -> bool {
-        !self.is_lower_bid_than(other) && !self.is_equivalent_bid_to(other)
+    #[cfg(feature = "crypto")]
+    fn mac(&self, key: &[u8]) -> hmac::Hmac<sha2::Sha256> {
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&self.canonical_bytes());
+        mac
+    }
+
+    // fixed field order with length-prefixed strings and big-endian numbers
+    // - timestamps are encoded as UTC seconds and nanoseconds, independent of their source format
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for field in &[&self.auction_id, &self.bidder_id] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field.as_bytes());
+        }
+        bytes.extend_from_slice(&self.id.to_be_bytes());
+        bytes.extend_from_slice(&self.amount.to_be_bytes());
+        bytes.extend_from_slice(&self.made_at.timestamp().to_be_bytes());
+        bytes.extend_from_slice(&self.made_at.timestamp_subsec_nanos().to_be_bytes());
+        match self.removed_at {
+            Some(removed_at) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&removed_at.timestamp().to_be_bytes());
+                bytes.extend_from_slice(&removed_at.timestamp_subsec_nanos().to_be_bytes());
+            }
+            None => bytes.push(0),
+        }
+        bytes.push(match self.removal_reason {
+            None => 0,
+            Some(RemovalReason::Withdrawn) => 1,
+            Some(RemovalReason::Expired) => 2,
+            Some(RemovalReason::Disqualified) => 3,
+            Some(RemovalReason::Outbid) => 4,
+        });
+        bytes
+    }
+
+    /// Derives an id from a bid's content for callers that don't supply one.
+    ///
+    /// The fields are hashed with 64-bit FNV-1a, which unlike `DefaultHasher` is fixed across
+    /// Rust releases, and folded into an `i32`. With only 2^32 possible ids, collisions are
+    /// likely once an auction holds tens of thousands of bids (about 50% at ~77,000), and two
+    /// identical bids always collide, so derived ids must not be relied on as unique keys.
+    pub fn derived_id(
+        auction_id: &str,
+        bidder_id: &str,
+        amount: i32,
+        made_at: chrono::DateTime<chrono::Utc>,
+    ) -> i32 {
+        let mut bytes = Vec::new();
+        for field in &[auction_id, bidder_id] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field.as_bytes());
+        }
+        bytes.extend_from_slice(&amount.to_be_bytes());
+        bytes.extend_from_slice(&made_at.timestamp().to_be_bytes());
+        bytes.extend_from_slice(&made_at.timestamp_subsec_nanos().to_be_bytes());
+        let hash = fnv1a_64(&bytes);
+        ((hash >> 32) ^ hash) as u32 as i32
+    }
+
+    pub fn can_outbid(&self, leader: &Self, increment: i32) -> Result<(), BidError> {
+        if !leader.is_active() {
+            return Err(BidError::LeaderInactive);
+        }
+        if !self.is_active() {
+            return Err(BidError::BidInactive);
+        }
+        if self.auction_id != leader.auction_id {
+            return Err(BidError::AuctionMismatch);
+        }
+
+        // widened so that a leader near i32::MAX cannot overflow the threshold
+        let minimum_amount = i64::from(leader.amount) + i64::from(increment);
+        if i64::from(self.amount) < minimum_amount {
+            return Err(BidError::BelowMinimumIncrement);
+        }
+        Ok(())
+    }
+
+    // a premium in basis points is added on top of the amount and truncated toward zero
+    pub fn effective_amount(&self, premium_bps: u32) -> i64 {
+        let premium = i128::from(self.amount) * i128::from(premium_bps) / 10_000;
+        (i128::from(self.amount) + premium) as i64
+    }
+
+    // only this bid carries the premium; other is compared at its plain amount
+    // - e.g. a bidder's total outlay against a competing bid placed without a premium
+    // - activity and then time still decide as in cmp_bids
+    pub fn is_lower_effective_than(&self, other: &Self, premium_bps: u32) -> bool {
+        if self.is_active() != other.is_active() {
+            return !self.is_active();
+        }
+        let effective_amount = self.effective_amount(premium_bps);
+        let other_amount = i64::from(other.amount);
+
+        effective_amount < other_amount || (effective_amount == other_amount && other.made_at < self.made_at)
+    }
+
+    // a draw is an equivalent pair: the same activity, amount and made_at
+    pub fn outcome_against(&self, other: &Bid) -> BidOutcome {
+        match self.cmp_bids(other) {
+            Ordering::Greater => BidOutcome::Wins,
+            Ordering::Less => BidOutcome::Loses,
+            Ordering::Equal => BidOutcome::Draw,
+        }
+    }
+
+    /// Compares bids after penalizing those made close to `auction_close`.
+    ///
+    /// A bid made less than `ANTISNIPE_WINDOW_SECONDS` before `auction_close`, or at or after it,
+    /// is ranked by `amount - amount * penalty_bps / 10_000`, truncated toward zero; any other
+    /// bid is ranked by its full amount. Activity and then `made_at` still decide as in
+    /// `cmp_bids`, so a penalized bid that ties with an earlier bid loses the tie.
+    pub fn is_lower_bid_than_antisnipe(&self, other: &Self, auction_close: chrono::DateTime<Utc>, penalty_bps: u32) -> bool {
+        if self.is_active() != other.is_active() {
+            return !self.is_active();
+        }
+        let penalized_amount = |bid: &Bid| {
+            let amount = i128::from(bid.amount);
+            if auction_close - bid.made_at < chrono::Duration::seconds(ANTISNIPE_WINDOW_SECONDS) {
+                amount - amount * i128::from(penalty_bps) / 10_000
+            } else {
+                amount
+            }
+        };
+        let amount = penalized_amount(self);
+        let other_amount = penalized_amount(other);
+
+        amount < other_amount || (amount == other_amount && other.made_at < self.made_at)
+    }
+
+    // the single source of truth for bid order; the is_*_bid_* predicates all derive from it
+    // - activity is checked once and decides alone when the bids differ in activity
+    // - otherwise the higher amount wins, then the earlier bid
+    #[inline]
+    pub fn cmp_bids(&self, other: &Self) -> Ordering {
+        match (self.is_active(), other.is_active()) {
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            _ => self.amount.cmp(&other.amount).then_with(|| other.made_at.cmp(&self.made_at)),
+        }
     }
 }
 
+// mirrors Bid so that strict parsing can reject unknown fields without changing Bid's own serde
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictBid {
+    auction_id: String,
+    bidder_id: String,
+    id: i32,
+    amount: i32,
+    made_at: chrono::DateTime<chrono::Utc>,
+    removed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    removal_reason: Option<RemovalReason>,
+}
+
+impl From<StrictBid> for Bid {
+    fn from(bid: StrictBid) -> Self {
+        Bid {
+            auction_id: bid.auction_id,
+            bidder_id: bid.bidder_id,
+            id: bid.id,
+            amount: bid.amount,
+            made_at: bid.made_at,
+            removed_at: bid.removed_at,
+            removal_reason: bid.removal_reason,
+        }
+    }
+}
+
+impl PartialOrd for Bid {
+    // bids only rank against bids in the same auction
+    // - equivalent but unequal bids are unordered to stay consistent with PartialEq
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.auction_id != other.auction_id {
+            return None;
+        }
+
+        match self.cmp_bids(other) {
+            Ordering::Equal if self != other => None,
+            ordering => Some(ordering),
+        }
+    }
+}
 
 #[cfg(test)]
 mod methods {
-    use crate::models::v1::bid::Bid;
-    use chrono::{Utc, DateTime, Duration, NaiveDateTime};
+    use crate::models::v1::bid::{Bid, ANTISNIPE_WINDOW_SECONDS};
+    use crate::models::v1::bid_error::BidError;
+    use crate::models::v1::bid_outcome::BidOutcome;
+    use crate::models::v1::comparison_explanation::ComparisonRule;
+    use crate::models::v1::removal_reason::RemovalReason;
+    use chrono::{Utc, DateTime, Duration};
+    use std::cmp::Ordering;
 
     #[test]
     fn new_works() {
@@ -110,6 +422,21 @@ mod methods {
 
         bid.remove();
         assert!(bid.removed_at.is_some());
+        assert!(bid.removal_reason.is_none());
+        assert!(!bid.is_active());
+    }
+
+    #[test]
+    fn remove_with_reason_works() {
+        let auction_id: String = String::from("auction_id");
+        let bidder_id: String = String::from("bidder_id");
+        let id: i32 = 0;
+        let amount: i32 = 10000;
+        let mut bid = Bid::new(auction_id.clone(), bidder_id.clone(), id, amount);
+
+        bid.remove_with_reason(RemovalReason::Expired);
+        assert!(bid.removed_at.is_some());
+        assert_eq!(Some(RemovalReason::Expired), bid.removal_reason);
         assert!(!bid.is_active());
     }
 
@@ -125,7 +452,7 @@ mod methods {
         }
 
         fn earlier_made_at() -> DateTime<Utc> {
-            DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(1000, 100), Utc)
+            DateTime::<Utc>::from_timestamp(1000, 100).unwrap()
         }
 
         fn later_made_at() -> DateTime<Utc> {
@@ -144,6 +471,7 @@ mod methods {
                 amount: TestData::lower_amount(),
                 made_at: TestData::earlier_made_at(),
                 removed_at: None,
+                removal_reason: None,
             }
         }
 
@@ -155,6 +483,7 @@ mod methods {
                 amount: TestData::lower_amount(),
                 made_at: TestData::later_made_at(),
                 removed_at: None,
+                removal_reason: None,
             }
         }
 
@@ -166,6 +495,7 @@ mod methods {
                 amount: TestData::higher_amount(),
                 made_at: TestData::earlier_made_at(),
                 removed_at: None,
+                removal_reason: None,
             }
         }
 
@@ -177,6 +507,7 @@ mod methods {
                 amount: TestData::higher_amount(),
                 made_at: TestData::later_made_at(),
                 removed_at: None,
+                removal_reason: None,
             }
         }
 
@@ -188,6 +519,7 @@ mod methods {
                 amount: TestData::lower_amount(),
                 made_at: TestData::earlier_made_at(),
                 removed_at: TestData::removed_at(),
+                removal_reason: None,
             }
         }
 
@@ -199,6 +531,7 @@ mod methods {
                 amount: TestData::lower_amount(),
                 made_at: TestData::later_made_at(),
                 removed_at: TestData::removed_at(),
+                removal_reason: None,
             }
         }
 
@@ -210,6 +543,7 @@ mod methods {
                 amount: TestData::higher_amount(),
                 made_at: TestData::earlier_made_at(),
                 removed_at: TestData::removed_at(),
+                removal_reason: None,
             }
         }
 
@@ -221,13 +555,14 @@ mod methods {
                 amount: TestData::higher_amount(),
                 made_at: TestData::later_made_at(),
                 removed_at: TestData::removed_at(),
+                removal_reason: None,
             }
         }
     }
 
     #[test]
     fn is_lower_bid_than_works() {
-        let bids = vec![
+        let bids = [
             TestData::inactive_lower_later_bid(),
             TestData::inactive_lower_earlier_bid(),
             TestData::inactive_higher_later_bid(),
@@ -238,11 +573,9 @@ mod methods {
             TestData::active_higher_earlier_bid(),
         ];
 
-        let mut index: usize = 0;
-        for lower_bid in &bids[0..bids.len()] {
-            index += 1;
-            for higher_bid in &bids[index..bids.len()] {
-                assert!(lower_bid.is_lower_bid_than(&higher_bid));
+        for (index, lower_bid) in bids.iter().enumerate() {
+            for higher_bid in &bids[index + 1..bids.len()] {
+                assert!(lower_bid.is_lower_bid_than(higher_bid));
             }
         }
     }
@@ -257,22 +590,21 @@ mod methods {
                 id: i,
                 amount: TestData::higher_amount(),
                 made_at: TestData::earlier_made_at(),
-                removed_at: None
+                removed_at: None,
+                removal_reason: None,
             })
         }
 
-        let mut index: usize = 0;
-        for follower in &bids[0..bids.len()] {
-            index += 1;
-            for leader in &bids[index..bids.len()] {
-                assert!(follower.is_equivalent_bid_to(&leader));
+        for (index, follower) in bids.iter().enumerate() {
+            for leader in &bids[index + 1..bids.len()] {
+                assert!(follower.is_equivalent_bid_to(leader));
             }
         }
     }
 
     #[test]
     fn is_higher_bid_than_works() {
-        let bids = vec![
+        let bids = [
             TestData::active_higher_earlier_bid(),
             TestData::active_higher_later_bid(),
             TestData::active_lower_earlier_bid(),
@@ -283,19 +615,400 @@ mod methods {
             TestData::inactive_lower_later_bid(),
         ];
 
-        let mut index: usize = 0;
-        for higher_bid in &bids[0..bids.len()] {
-            index += 1;
-            for lower_bid in &bids[index..bids.len()] {
-                assert!(higher_bid.is_higher_bid_than(&lower_bid));
+        for (index, higher_bid) in bids.iter().enumerate() {
+            for lower_bid in &bids[index + 1..bids.len()] {
+                assert!(higher_bid.is_higher_bid_than(lower_bid));
+            }
+        }
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    fn made_at_in_works() {
+        use chrono::Offset;
+
+        let mut bid = TestData::active_lower_earlier_bid();
+        bid.made_at = DateTime::<Utc>::from_timestamp(1_600_000_000, 0).unwrap();
+
+        let made_at = bid.made_at_in(chrono_tz::America::New_York);
+        assert_eq!(-4 * 3600, made_at.offset().fix().local_minus_utc());
+        assert_eq!(bid.made_at, made_at.with_timezone(&Utc));
+    }
+
+    #[test]
+    fn cmp_bids_works() {
+        let lower_bid = TestData::active_lower_earlier_bid();
+        let higher_bid = TestData::active_higher_earlier_bid();
+
+        assert_eq!(Ordering::Less, lower_bid.cmp_bids(&higher_bid));
+        assert_eq!(Ordering::Equal, lower_bid.cmp_bids(&lower_bid));
+        assert_eq!(Ordering::Greater, higher_bid.cmp_bids(&lower_bid));
+    }
+
+    #[test]
+    fn partial_cmp_agrees_with_is_lower_bid_than() {
+        // a field-order derive would rank these by bidder_id ("0" < "7") and call the active bid lower
+        let active_bid = TestData::active_lower_earlier_bid();
+        let removed_bid = TestData::inactive_higher_later_bid();
+        assert!(removed_bid.is_lower_bid_than(&active_bid));
+        assert!(removed_bid < active_bid);
+
+        // and by bidder_id ("2" < "3") here, calling the earlier bid lower
+        let earlier_bid = TestData::active_higher_earlier_bid();
+        let later_bid = TestData::active_higher_later_bid();
+        assert!(later_bid.is_lower_bid_than(&earlier_bid));
+        assert!(later_bid < earlier_bid);
+        assert!(earlier_bid > later_bid);
+    }
+
+    #[test]
+    fn partial_cmp_works() {
+        let bid = TestData::active_lower_earlier_bid();
+        assert_eq!(Some(Ordering::Equal), bid.partial_cmp(&TestData::active_lower_earlier_bid()));
+
+        let mut equivalent_bid = TestData::active_lower_earlier_bid();
+        equivalent_bid.id = 100;
+        assert!(bid.is_equivalent_bid_to(&equivalent_bid));
+        assert_eq!(None, bid.partial_cmp(&equivalent_bid));
+
+        let mut other_auction_bid = TestData::active_higher_earlier_bid();
+        other_auction_bid.auction_id = String::from("other_auction_id");
+        assert_eq!(None, bid.partial_cmp(&other_auction_bid));
+    }
+
+    #[test]
+    fn update_amount_works() {
+        let mut bid = TestData::active_lower_earlier_bid();
+        assert_eq!(Ok(()), bid.update_amount(500));
+        assert_eq!(500, bid.amount);
+
+        let mut removed_bid = TestData::inactive_lower_earlier_bid();
+        assert_eq!(Err(BidError::BidInactive), removed_bid.update_amount(500));
+        assert_eq!(TestData::lower_amount(), removed_bid.amount);
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn sign_and_verify_work() {
+        let key = b"key";
+        let mut bid = TestData::active_lower_earlier_bid();
+        let sig = bid.sign(key);
+
+        assert!(bid.verify(key, &sig));
+        assert!(!bid.verify(b"other_key", &sig));
+        assert!(!bid.verify(key, "not hex"));
+
+        bid.amount += 1;
+        assert!(!bid.verify(key, &sig));
+    }
+
+    #[test]
+    fn canonical_bytes_works() {
+        let bid = TestData::active_lower_earlier_bid();
+        let data = r#"{
+            "removed_at": null,
+            "made_at": "1970-01-01T01:16:40.000000100+01:00",
+            "amount": 0,
+            "id": 0,
+            "bidder_id": "0",
+            "auction_id": "auction_id"
+        }"#;
+        let deserialized_bid = serde_json::from_str::<Bid>(data).unwrap();
+
+        assert_eq!(bid.canonical_bytes(), deserialized_bid.canonical_bytes());
+        assert_ne!(
+            bid.canonical_bytes(),
+            TestData::active_lower_later_bid().canonical_bytes()
+        );
+    }
+
+    #[test]
+    fn derived_id_works() {
+        let bid = TestData::active_lower_earlier_bid();
+        let id = Bid::derived_id(&bid.auction_id, &bid.bidder_id, bid.amount, bid.made_at);
+
+        assert_eq!(id, Bid::derived_id("auction_id", "0", bid.amount, bid.made_at));
+        assert_ne!(id, Bid::derived_id(&bid.auction_id, &bid.bidder_id, bid.amount + 1, bid.made_at));
+        assert_ne!(id, Bid::derived_id(&bid.auction_id, "1", bid.amount, bid.made_at));
+        assert_ne!(id, Bid::derived_id(&bid.auction_id, &bid.bidder_id, bid.amount, TestData::later_made_at()));
+        // length prefixes keep field boundaries from being shifted into a collision
+        assert_ne!(Bid::derived_id("ab", "c", 0, bid.made_at), Bid::derived_id("a", "bc", 0, bid.made_at));
+    }
+
+    #[test]
+    fn is_equivalent_bid_to_is_exhaustive_across_activity_and_time() {
+        let bids = [
+            TestData::inactive_lower_later_bid(),
+            TestData::inactive_lower_earlier_bid(),
+            TestData::inactive_higher_later_bid(),
+            TestData::inactive_higher_earlier_bid(),
+            TestData::active_lower_later_bid(),
+            TestData::active_lower_earlier_bid(),
+            TestData::active_higher_later_bid(),
+            TestData::active_higher_earlier_bid(),
+        ];
+
+        for bid in &bids {
+            for other in &bids {
+                let expected = bid.is_active() == other.is_active()
+                    && bid.amount == other.amount
+                    && bid.made_at == other.made_at;
+                assert_eq!(expected, bid.is_equivalent_bid_to(other));
+            }
+        }
+
+        let active_bid = TestData::active_lower_earlier_bid();
+        let removed_bid = TestData::inactive_lower_earlier_bid();
+        assert!(!active_bid.is_equivalent_bid_to(&removed_bid));
+        assert!(!removed_bid.is_equivalent_bid_to(&active_bid));
+        assert!(removed_bid.is_lower_bid_than(&active_bid));
+    }
+
+    #[test]
+    fn can_outbid_works() {
+        let leader = TestData::active_lower_earlier_bid();
+        let mut bid = TestData::active_higher_later_bid();
+
+        assert_eq!(Ok(()), bid.can_outbid(&leader, 1));
+        bid.amount = i32::MAX;
+        assert_eq!(Ok(()), bid.can_outbid(&leader, i32::MAX));
+    }
+
+    #[test]
+    fn can_outbid_rejects_amounts_below_minimum_increment() {
+        let leader = TestData::active_lower_earlier_bid();
+        let mut bid = TestData::active_higher_later_bid();
+        assert_eq!(Err(BidError::BelowMinimumIncrement), bid.can_outbid(&leader, 2));
+
+        bid.amount = i32::MAX;
+        let mut high_leader = TestData::active_lower_earlier_bid();
+        high_leader.amount = i32::MAX;
+        assert_eq!(Err(BidError::BelowMinimumIncrement), bid.can_outbid(&high_leader, 1));
+    }
+
+    #[test]
+    fn can_outbid_rejects_inactive_leader() {
+        let leader = TestData::inactive_lower_earlier_bid();
+        let bid = TestData::active_higher_later_bid();
+
+        assert_eq!(Err(BidError::LeaderInactive), bid.can_outbid(&leader, 1));
+    }
+
+    #[test]
+    fn can_outbid_rejects_inactive_bid() {
+        let leader = TestData::active_lower_earlier_bid();
+        let bid = TestData::inactive_higher_later_bid();
+
+        assert_eq!(Err(BidError::BidInactive), bid.can_outbid(&leader, 1));
+    }
+
+    #[test]
+    fn can_outbid_rejects_other_auction() {
+        let leader = TestData::active_lower_earlier_bid();
+        let mut bid = TestData::active_higher_later_bid();
+        bid.auction_id = String::from("other_auction_id");
+
+        assert_eq!(Err(BidError::AuctionMismatch), bid.can_outbid(&leader, 1));
+    }
+
+    #[test]
+    fn normalized_works() {
+        let bid = Bid::new(String::from("  auction_id\t"), String::from(" bidder_id "), 0, 10000);
+        let normalized_bid = bid.normalized();
+
+        assert_eq!("auction_id", normalized_bid.auction_id);
+        assert_eq!("bidder_id", normalized_bid.bidder_id);
+        assert_eq!(bid.amount, normalized_bid.amount);
+        assert_eq!(bid.made_at, normalized_bid.made_at);
+        assert_eq!(Ok(normalized_bid), bid.normalized_strict());
+    }
+
+    #[test]
+    fn normalized_strict_rejects_empty_ids() {
+        let blank_auction = Bid::new(String::from("   "), String::from("bidder_id"), 0, 10000);
+        let blank_bidder = Bid::new(String::from("auction_id"), String::from(""), 0, 10000);
+
+        assert_eq!(Err(BidError::EmptyId), blank_auction.normalized_strict());
+        assert_eq!(Err(BidError::EmptyId), blank_bidder.normalized_strict());
+        assert_eq!("", blank_auction.normalized().auction_id);
+    }
+
+    #[test]
+    fn explain_comparison_works() {
+        let explanation = TestData::active_lower_later_bid().explain_comparison(&TestData::active_lower_earlier_bid());
+        assert_eq!(ComparisonRule::TimeTieBreak, explanation.rule);
+        assert_eq!(Ordering::Less, explanation.ordering);
+
+        let explanation = TestData::active_higher_later_bid().explain_comparison(&TestData::active_lower_earlier_bid());
+        assert_eq!(ComparisonRule::AmountDifference { difference: 1 }, explanation.rule);
+        assert_eq!(Ordering::Greater, explanation.ordering);
+
+        let explanation = TestData::inactive_higher_earlier_bid().explain_comparison(&TestData::active_lower_later_bid());
+        assert_eq!(ComparisonRule::ActivityOverride, explanation.rule);
+        assert_eq!(Ordering::Less, explanation.ordering);
+
+        let explanation = TestData::active_lower_earlier_bid().explain_comparison(&TestData::active_lower_earlier_bid());
+        assert_eq!(ComparisonRule::Equivalent, explanation.rule);
+        assert_eq!(Ordering::Equal, explanation.ordering);
+    }
+
+    #[test]
+    fn validate_works() {
+        assert_eq!(Ok(()), TestData::active_lower_earlier_bid().validate());
+
+        let mut future_bid = TestData::active_lower_earlier_bid();
+        future_bid.made_at = Utc::now() + Duration::days(1);
+        assert_eq!(Err(BidError::FutureDated), future_bid.validate());
+
+        let mut blank_bid = TestData::active_lower_earlier_bid();
+        blank_bid.bidder_id = String::new();
+        assert_eq!(Err(BidError::EmptyId), blank_bid.validate());
+    }
+
+    // the predicates as they were written before they derived from cmp_bids
+    fn legacy_is_lower_bid_than(bid: &Bid, other: &Bid) -> bool {
+        let both_bids_active = bid.is_active() && other.is_active();
+        let neither_bid_active = !(bid.is_active() || other.is_active());
+        let is_lower_amount = bid.amount < other.amount;
+        let is_equal_amount = bid.amount == other.amount;
+        let is_later_bid = other.made_at < bid.made_at;
+
+        if both_bids_active || neither_bid_active {
+            is_lower_amount || (is_equal_amount && is_later_bid)
+        } else {
+            !bid.is_active()
+        }
+    }
+
+    fn legacy_is_equivalent_bid_to(bid: &Bid, other: &Bid) -> bool {
+        let both_bids_active = bid.is_active() && other.is_active();
+        let neither_bid_active = !(bid.is_active() || other.is_active());
+
+        if both_bids_active || neither_bid_active {
+            bid.amount == other.amount && other.made_at == bid.made_at
+        } else {
+            false
+        }
+    }
+
+    #[test]
+    fn predicates_match_legacy_implementation() {
+        let bids = [
+            TestData::inactive_lower_later_bid(),
+            TestData::inactive_lower_earlier_bid(),
+            TestData::inactive_higher_later_bid(),
+            TestData::inactive_higher_earlier_bid(),
+            TestData::active_lower_later_bid(),
+            TestData::active_lower_earlier_bid(),
+            TestData::active_higher_later_bid(),
+            TestData::active_higher_earlier_bid(),
+        ];
+
+        for bid in &bids {
+            for other in &bids {
+                let is_lower = legacy_is_lower_bid_than(bid, other);
+                let is_equivalent = legacy_is_equivalent_bid_to(bid, other);
+                assert_eq!(is_lower, bid.is_lower_bid_than(other));
+                assert_eq!(is_equivalent, bid.is_equivalent_bid_to(other));
+                assert_eq!(!is_lower && !is_equivalent, bid.is_higher_bid_than(other));
             }
         }
     }
+
+    #[test]
+    fn is_equivalent_bid_to_within_works() {
+        let bid = TestData::active_lower_earlier_bid();
+        let mut skewed_bid = TestData::active_lower_earlier_bid();
+        skewed_bid.made_at = bid.made_at + Duration::microseconds(250);
+
+        assert!(bid.is_equivalent_bid_to_within(&skewed_bid, Duration::milliseconds(1)));
+        assert!(skewed_bid.is_equivalent_bid_to_within(&bid, Duration::milliseconds(1)));
+        assert!(!bid.is_equivalent_bid_to_within(&skewed_bid, Duration::zero()));
+        assert!(bid.is_equivalent_bid_to_within(&bid, Duration::zero()));
+
+        skewed_bid.remove();
+        assert!(!bid.is_equivalent_bid_to_within(&skewed_bid, Duration::milliseconds(1)));
+    }
+
+    #[test]
+    fn effective_amount_works() {
+        let mut bid = TestData::active_lower_earlier_bid();
+        bid.amount = 10000;
+        assert_eq!(10000, bid.effective_amount(0));
+        assert_eq!(11500, bid.effective_amount(1500));
+
+        bid.amount = 199;
+        assert_eq!(200, bid.effective_amount(100));
+
+        bid.amount = i32::MAX;
+        assert_eq!(2 * i64::from(i32::MAX), bid.effective_amount(10_000));
+    }
+
+    #[test]
+    fn is_lower_effective_than_works() {
+        let mut bid = TestData::active_lower_later_bid();
+        bid.amount = 10000;
+        let mut other = TestData::active_lower_earlier_bid();
+        other.amount = 10500;
+
+        assert!(bid.is_lower_effective_than(&other, 0));
+        assert!(bid.is_lower_effective_than(&other, 499));
+        // at the threshold the amounts tie and the earlier other bid still wins
+        assert!(bid.is_lower_effective_than(&other, 500));
+        assert!(!bid.is_lower_effective_than(&other, 501));
+
+        other.remove();
+        assert!(!bid.is_lower_effective_than(&other, 0));
+    }
+
+    #[test]
+    fn is_lower_bid_than_antisnipe_works() {
+        let auction_close = TestData::earlier_made_at() + Duration::hours(1);
+        let mut early_bid = TestData::active_lower_earlier_bid();
+        early_bid.amount = 1000;
+        let mut snipe_bid = TestData::active_lower_later_bid();
+        snipe_bid.amount = 1040;
+        snipe_bid.made_at = auction_close - Duration::seconds(5);
+
+        assert!(early_bid.is_lower_bid_than(&snipe_bid));
+        assert!(snipe_bid.is_lower_bid_than_antisnipe(&early_bid, auction_close, 500));
+        assert!(!early_bid.is_lower_bid_than_antisnipe(&snipe_bid, auction_close, 500));
+        assert!(!snipe_bid.is_lower_bid_than_antisnipe(&early_bid, auction_close, 0));
+    }
+
+    #[test]
+    fn is_lower_bid_than_antisnipe_ignores_bids_outside_window() {
+        let auction_close = TestData::earlier_made_at() + Duration::hours(1);
+        let mut early_bid = TestData::active_lower_earlier_bid();
+        early_bid.amount = 1000;
+        let mut late_bid = TestData::active_lower_later_bid();
+        late_bid.amount = 1040;
+        late_bid.made_at = auction_close - Duration::seconds(ANTISNIPE_WINDOW_SECONDS);
+
+        assert!(!late_bid.is_lower_bid_than_antisnipe(&early_bid, auction_close, 500));
+        assert!(early_bid.is_lower_bid_than_antisnipe(&late_bid, auction_close, 500));
+    }
+
+    #[test]
+    fn outcome_against_works() {
+        let lower_bid = TestData::active_lower_earlier_bid();
+        let higher_bid = TestData::active_higher_earlier_bid();
+        let mut simultaneous_bid = TestData::active_lower_earlier_bid();
+        simultaneous_bid.id = 8;
+        simultaneous_bid.bidder_id = String::from("8");
+
+        assert_eq!(BidOutcome::Wins, higher_bid.outcome_against(&lower_bid));
+        assert_eq!(BidOutcome::Loses, lower_bid.outcome_against(&higher_bid));
+        assert_eq!(BidOutcome::Draw, lower_bid.outcome_against(&simultaneous_bid));
+        assert_eq!(BidOutcome::Draw, simultaneous_bid.outcome_against(&lower_bid));
+        assert_eq!(BidOutcome::Loses, TestData::inactive_higher_earlier_bid().outcome_against(&lower_bid));
+    }
 }
 
 #[cfg(test)]
 mod serialization_and_deserialization {
     use crate::models::v1::bid::Bid;
+    use crate::models::v1::bid_error::BidError;
 
     #[test]
     fn it_can_serialize_and_deserialize() {
@@ -315,4 +1028,129 @@ mod serialization_and_deserialization {
         let deserialized_bid = result_of_deserialization.unwrap();
         assert_eq!(bid, deserialized_bid);
     }
+
+    #[test]
+    fn from_json_accepts_unknown_fields() {
+        let data = r#"{
+            "auction_id": "auction_id",
+            "bidder_id": "bidder_id",
+            "id": 0,
+            "amount": 10000,
+            "made_at": "1970-01-01T00:16:40Z",
+            "removed_at": null,
+            "unknown": true
+        }"#;
+
+        let bid = Bid::from_json(data).unwrap();
+        assert_eq!(10000, bid.amount);
+        assert!(Bid::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn from_json_strict_rejects_unknown_fields() {
+        let data = r#"{
+            "auction_id": "auction_id",
+            "bidder_id": "bidder_id",
+            "id": 0,
+            "amount": 10000,
+            "made_at": "1970-01-01T00:16:40Z",
+            "removed_at": null,
+            "unknown": true
+        }"#;
+
+        let result = Bid::from_json_strict(data);
+        assert!(matches!(
+            result,
+            Err(BidError::InvalidJson(message)) if message.contains("unknown")
+        ));
+
+        let auction_id: String = String::from("auction_id");
+        let bidder_id: String = String::from("bidder_id");
+        let bid = Bid::new(auction_id, bidder_id, 0, 10000);
+        let data = serde_json::to_string(&bid).unwrap();
+        assert_eq!(Ok(bid), Bid::from_json_strict(&data));
+    }
+
+    #[test]
+    fn to_json_deterministic_works() {
+        let bid = Bid {
+            auction_id: String::from("auction_id"),
+            bidder_id: String::from("bidder_id"),
+            id: 0,
+            amount: 10000,
+            made_at: chrono::DateTime::<chrono::Utc>::from_timestamp(1000, 100).unwrap(),
+            removed_at: None,
+            removal_reason: None,
+        };
+
+        let expected = concat!(
+            r#"{"amount":10000,"auction_id":"auction_id","bidder_id":"bidder_id","id":0,"#,
+            r#""made_at":"1970-01-01T00:16:40.000000100Z","removal_reason":null,"removed_at":null}"#
+        );
+        assert_eq!(Ok(String::from(expected)), bid.to_json_deterministic());
+        assert_eq!(bid.to_json_deterministic(), bid.clone().to_json_deterministic());
+    }
+
+    #[test]
+    fn comparisons_survive_round_trip() {
+        // timestamps one nanosecond apart must still tie-break the same way after parsing
+        let made_at = chrono::DateTime::<chrono::Utc>::from_timestamp(1000, 999_999_999).unwrap();
+        let mut bids = Vec::new();
+        for (id, amount, offset_nanos, removed) in [(0, 100, 0, false), (1, 100, 1, false), (2, 101, 1, false), (3, 100, 0, true), (4, 100, 1, true)] {
+            let mut bid = Bid::new(String::from("auction_id"), String::from("bidder_id"), id, amount);
+            bid.made_at = made_at + chrono::Duration::nanoseconds(offset_nanos);
+            if removed {
+                bid.removed_at = Some(made_at + chrono::Duration::nanoseconds(7));
+            }
+            bids.push(bid);
+        }
+        let round_tripped: Vec<Bid> = bids
+            .iter()
+            .map(|bid| serde_json::from_str::<Bid>(&serde_json::to_string(bid).unwrap()).unwrap())
+            .collect();
+
+        for (a, round_tripped_a) in bids.iter().zip(&round_tripped) {
+            for (b, round_tripped_b) in bids.iter().zip(&round_tripped) {
+                assert_eq!(a.is_lower_bid_than(b), round_tripped_a.is_lower_bid_than(round_tripped_b));
+                assert_eq!(a.is_equivalent_bid_to(b), round_tripped_a.is_equivalent_bid_to(round_tripped_b));
+                assert_eq!(a.is_higher_bid_than(b), round_tripped_a.is_higher_bid_than(round_tripped_b));
+            }
+        }
+        assert_eq!(bids, round_tripped);
+    }
+}
+
+#[cfg(all(test, feature = "bincode"))]
+mod bincode_serialization_and_deserialization {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bid_error::BidError;
+    use crate::models::v1::removal_reason::RemovalReason;
+
+    #[test]
+    fn it_can_round_trip_through_bincode() {
+        let mut bid = Bid::new(String::from("auction_id"), String::from("bidder_id"), 0, 10000);
+        let bytes = bid.to_bincode().unwrap();
+        assert_eq!(Ok(bid.clone()), Bid::from_bincode(&bytes));
+
+        bid.remove_with_reason(RemovalReason::Outbid);
+        let bytes = bid.to_bincode().unwrap();
+        assert_eq!(Ok(bid), Bid::from_bincode(&bytes));
+    }
+
+    #[test]
+    fn bincode_is_smaller_than_json() {
+        let bid = Bid::new(String::from("auction_id"), String::from("bidder_id"), 0, 10000);
+
+        let bincode_len = bid.to_bincode().unwrap().len();
+        let json_len = serde_json::to_vec(&bid).unwrap().len();
+        assert!(bincode_len < json_len, "{} >= {}", bincode_len, json_len);
+    }
+
+    #[test]
+    fn from_bincode_rejects_truncated_bytes() {
+        let bid = Bid::new(String::from("auction_id"), String::from("bidder_id"), 0, 10000);
+        let bytes = bid.to_bincode().unwrap();
+
+        assert!(matches!(Bid::from_bincode(&bytes[..bytes.len() / 2]), Err(BidError::InvalidBincode(_))));
+    }
 }
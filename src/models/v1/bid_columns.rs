@@ -0,0 +1,31 @@
+use crate::models::v1::bid::Bid;
+use chrono::{DateTime, Utc};
+
+// one Vec per Bid field, all the same length, where index i across them describes one bid
+// - removed_at and removal_reason are left out, since only active bids are collected
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BidColumns {
+    pub auction_ids: Vec<String>,
+    pub bidder_ids: Vec<String>,
+    pub ids: Vec<i32>,
+    pub amounts: Vec<i32>,
+    pub made_ats: Vec<DateTime<Utc>>,
+}
+
+impl BidColumns {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn push(&mut self, bid: &Bid) {
+        self.auction_ids.push(bid.auction_id.clone());
+        self.bidder_ids.push(bid.bidder_id.clone());
+        self.ids.push(bid.id);
+        self.amounts.push(bid.amount);
+        self.made_ats.push(bid.made_at);
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum BidError {
+    BidderLimitReached,
+    Overflow,
+    BidInactive,
+    PrecisionLoss,
+    RateLimited,
+    BidFrozen,
+    InvalidJson(String),
+    Serialization(String),
+    LeaderInactive,
+    AuctionMismatch,
+    BelowMinimumIncrement,
+    EmptyId,
+    FutureDated,
+    InvalidBidAt(usize, Box<BidError>),
+    InvalidLine(usize, Box<BidError>),
+    Io(String),
+    InvalidProto(String),
+    InvalidBincode(String),
+    InvalidTick,
+    CurrencyMismatch,
+    AuctionPaused,
+    GraceExpired,
+    BidNotFound,
+    IdOverflow,
+    InvalidBucket,
+    InvalidRangeSize,
+    InvalidIncrement,
+    AuctionClosed,
+}
+
+impl fmt::Display for BidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BidError::BidderLimitReached => write!(f, "bidder has reached the active bid limit"),
+            BidError::Overflow => write!(f, "bid amount overflowed"),
+            BidError::BidInactive => write!(f, "bid has been removed"),
+            BidError::PrecisionLoss => write!(
+                f,
+                "bid amount cannot be represented without losing precision"
+            ),
+            BidError::RateLimited => write!(f, "bidder is bidding too frequently"),
+            BidError::BidFrozen => write!(f, "bid is frozen"),
+            BidError::InvalidJson(message) => write!(f, "invalid bid json: {}", message),
+            BidError::Serialization(message) => write!(f, "bid serialization failed: {}", message),
+            BidError::LeaderInactive => write!(f, "leading bid has been removed"),
+            BidError::AuctionMismatch => write!(f, "bids belong to different auctions"),
+            BidError::BelowMinimumIncrement => write!(f, "bid does not meet the minimum increment"),
+            BidError::EmptyId => write!(f, "bid has an empty auction or bidder id"),
+            BidError::FutureDated => write!(f, "bid is dated in the future"),
+            BidError::InvalidBidAt(index, error) => {
+                write!(f, "bid at index {} is invalid: {}", index, error)
+            }
+            BidError::InvalidLine(line, error) => write!(f, "line {} is invalid: {}", line, error),
+            BidError::Io(message) => write!(f, "bid i/o failed: {}", message),
+            BidError::InvalidProto(message) => write!(f, "invalid bid protobuf: {}", message),
+            BidError::InvalidBincode(message) => write!(f, "invalid bid bincode: {}", message),
+            BidError::InvalidTick => write!(f, "tick size must be positive"),
+            BidError::CurrencyMismatch => write!(f, "bids are in different currencies"),
+            BidError::AuctionPaused => write!(f, "auction is paused"),
+            BidError::GraceExpired => write!(f, "retraction grace period has expired"),
+            BidError::BidNotFound => write!(f, "no active bid with that id"),
+            BidError::IdOverflow => write!(f, "bid id overflowed"),
+            BidError::InvalidBucket => write!(f, "bucket must be a positive duration"),
+            BidError::InvalidRangeSize => write!(f, "bid id range size must be positive"),
+            BidError::InvalidIncrement => write!(f, "increment must not be negative"),
+            BidError::AuctionClosed => write!(f, "bid was made after the auction closed"),
+        }
+    }
+}
+
+impl std::error::Error for BidError {}
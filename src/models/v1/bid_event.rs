@@ -0,0 +1,19 @@
+use crate::models::v1::bid::Bid;
+use chrono::{DateTime, Utc};
+
+// one entry in an auction's event log, replayed in order to rebuild heap state
+// - a placed bid happened at its made_at; a removal carries its own time
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum BidEvent {
+    Placed(Bid),
+    Removed { id: i32, at: DateTime<Utc> },
+}
+
+impl BidEvent {
+    pub fn at(&self) -> DateTime<Utc> {
+        match self {
+            BidEvent::Placed(bid) => bid.made_at,
+            BidEvent::Removed { at, .. } => *at,
+        }
+    }
+}
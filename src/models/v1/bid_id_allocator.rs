@@ -0,0 +1,120 @@
+use crate::models::v1::bid_error::BidError;
+use std::collections::HashMap;
+use std::ops::Range;
+
+// hands out bid ids from ranges of range_size reserved per bidder, starting at 0
+// - a bidder's first id reserves their first range; using it up reserves the next free one
+// - ranges never overlap, so ids from one allocator are unique across all bidders
+// - IdOverflow is returned once the non-negative i32 ids have all been reserved
+#[derive(Clone, Debug)]
+pub struct BidIdAllocator {
+    range_size: i32,
+    next_range_start: i32,
+    bidders: HashMap<String, BidderIds>,
+}
+
+#[derive(Clone, Debug)]
+struct BidderIds {
+    ranges: Vec<Range<i32>>,
+    next: i32,
+}
+
+impl BidIdAllocator {
+    pub fn new(range_size: i32) -> Result<Self, BidError> {
+        if range_size <= 0 {
+            return Err(BidError::InvalidRangeSize);
+        }
+        Ok(BidIdAllocator {
+            range_size,
+            next_range_start: 0,
+            bidders: HashMap::new(),
+        })
+    }
+
+    pub fn allocate(&mut self, bidder_id: &str) -> Result<i32, BidError> {
+        let needs_range = match self.bidders.get(bidder_id) {
+            Some(ids) => ids.next == ids.ranges[ids.ranges.len() - 1].end,
+            None => true,
+        };
+        if needs_range {
+            let range = self.reserve_range()?;
+            let ids = self
+                .bidders
+                .entry(bidder_id.to_string())
+                .or_insert_with(|| BidderIds {
+                    ranges: Vec::new(),
+                    next: range.start,
+                });
+            ids.next = range.start;
+            ids.ranges.push(range);
+        }
+
+        let ids = self
+            .bidders
+            .get_mut(bidder_id)
+            .expect("a range was reserved above");
+        let id = ids.next;
+        ids.next += 1;
+        Ok(id)
+    }
+
+    pub fn ranges(&self, bidder_id: &str) -> &[Range<i32>] {
+        self.bidders
+            .get(bidder_id)
+            .map_or(&[], |ids| ids.ranges.as_slice())
+    }
+
+    fn reserve_range(&mut self) -> Result<Range<i32>, BidError> {
+        let start = self.next_range_start;
+        let end = start
+            .checked_add(self.range_size)
+            .ok_or(BidError::IdOverflow)?;
+        self.next_range_start = end;
+        Ok(start..end)
+    }
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1::bid_error::BidError;
+    use crate::models::v1::bid_id_allocator::BidIdAllocator;
+
+    #[test]
+    fn allocate_works() {
+        let mut allocator = BidIdAllocator::new(3).unwrap();
+
+        assert_eq!(Ok(0), allocator.allocate("a"));
+        assert_eq!(Ok(3), allocator.allocate("b"));
+        assert_eq!(Ok(1), allocator.allocate("a"));
+        assert_eq!(Ok(2), allocator.allocate("a"));
+        assert_eq!(Ok(6), allocator.allocate("a"));
+        assert_eq!(Ok(4), allocator.allocate("b"));
+
+        assert_eq!(&[0..3, 6..9], allocator.ranges("a"));
+        assert_eq!(Some(&(3..6)), allocator.ranges("b").first());
+        assert_eq!(1, allocator.ranges("b").len());
+        assert!(allocator.ranges("c").is_empty());
+    }
+
+    #[test]
+    fn new_rejects_empty_ranges() {
+        assert_eq!(
+            Some(BidError::InvalidRangeSize),
+            BidIdAllocator::new(0).err()
+        );
+        assert_eq!(
+            Some(BidError::InvalidRangeSize),
+            BidIdAllocator::new(-1).err()
+        );
+    }
+
+    #[test]
+    fn allocate_reports_exhausted_ids() {
+        let mut allocator = BidIdAllocator::new(i32::MAX / 2 + 1).unwrap();
+
+        assert_eq!(Ok(0), allocator.allocate("a"));
+        assert_eq!(Err(BidError::IdOverflow), allocator.allocate("b"));
+        assert_eq!(Ok(1), allocator.allocate("a"));
+        assert!(allocator.ranges("b").is_empty());
+    }
+}
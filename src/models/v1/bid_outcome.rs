@@ -0,0 +1,6 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BidOutcome {
+    Wins,
+    Loses,
+    Draw,
+}
@@ -0,0 +1,4003 @@
+use crate::models::v1::bid::{fnv1a_64, Bid};
+use crate::models::v1::bid_columns::BidColumns;
+use crate::models::v1::bid_error::BidError;
+use crate::models::v1::bid_event::BidEvent;
+use crate::models::v1::bid_id_allocator::BidIdAllocator;
+use crate::models::v1::heap_snapshot::{HeapDiff, HeapSnapshot};
+use crate::models::v1::removal_reason::RemovalReason;
+use crate::models::v1::tie_break::TieBreak;
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+
+pub struct BiddingHeap<const D: usize = 2> {
+    bids: Vec<Bid>,
+    tombstones: Vec<Bid>,
+    tie_break: TieBreak,
+    on_leader_change: Option<LeaderChangeCallback>,
+}
+
+type LeaderChangeCallback = Box<dyn FnMut(&Bid)>;
+
+impl<const D: usize> Default for BiddingHeap<D> {
+    // written out so that default heaps go through the arity check as well
+    fn default() -> Self {
+        Self::with_arity()
+    }
+}
+
+impl<const D: usize> std::fmt::Debug for BiddingHeap<D> {
+    // the callback has no useful Debug output, so only whether one is registered is shown
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BiddingHeap")
+            .field("bids", &self.bids)
+            .field("tombstones", &self.tombstones)
+            .field("tie_break", &self.tie_break)
+            .field("on_leader_change", &self.on_leader_change.is_some())
+            .finish()
+    }
+}
+
+impl BiddingHeap {
+    pub fn new() -> Self {
+        BiddingHeap::with_arity()
+    }
+
+    pub fn with_tie_break(tie_break: TieBreak) -> Self {
+        BiddingHeap::with_arity_and_tie_break(tie_break)
+    }
+
+    pub fn leader_history(events: &[BidEvent]) -> Vec<(DateTime<Utc>, Bid)> {
+        // the heap-wide active leader after each event, recorded only when it changes
+        let mut heap = BiddingHeap::new();
+        let mut history: Vec<(DateTime<Utc>, Bid)> = Vec::new();
+        for event in events {
+            match event {
+                BidEvent::Placed(bid) => heap.push(bid.clone()),
+                BidEvent::Removed { id, .. } => {
+                    heap.remove(*id);
+                }
+            }
+            let leader = match heap.active_view().peek() {
+                Some(leader) => leader,
+                None => continue,
+            };
+            let unchanged = history.last().is_some_and(|(_, previous)| {
+                previous.auction_id == leader.auction_id && previous.id == leader.id
+            });
+            if !unchanged {
+                history.push((event.at(), leader.clone()));
+            }
+        }
+        history
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, BidError> {
+        let bids = serde_json::from_str::<Vec<Bid>>(s)
+            .map_err(|e| BidError::InvalidJson(e.to_string()))?;
+        for (index, bid) in bids.iter().enumerate() {
+            bid.validate()
+                .map_err(|e| BidError::InvalidBidAt(index, Box::new(e)))?;
+        }
+        Ok(BiddingHeap::from(bids))
+    }
+
+    #[cfg(feature = "bincode")]
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, BidError> {
+        bincode::deserialize::<HeapSnapshot>(bytes)
+            .map(BiddingHeap::from)
+            .map_err(|e| BidError::InvalidBincode(e.to_string()))
+    }
+
+    pub fn from_ndjson(reader: impl BufRead) -> Result<Self, BidError> {
+        // line numbers in errors are one-based; blank lines are skipped
+        let mut bids = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.map_err(|e| {
+                BidError::InvalidLine(line_number, Box::new(BidError::Io(e.to_string())))
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let bid = Bid::from_json(&line)
+                .map_err(|e| BidError::InvalidLine(line_number, Box::new(e)))?;
+            bids.push(bid);
+        }
+        Ok(BiddingHeap::from(bids))
+    }
+}
+
+impl<const D: usize> BiddingHeap<D> {
+    // the heap keeps the highest bid, per its TieBreak (earliest wins by default), at the root
+    // - removed bids stay in the heap and sink below the active ones
+    // - each node has up to D children; wider heaps are shallower at the cost of more
+    //   comparisons per level
+
+    const VALID_ARITY: () = assert!(
+        D >= 2,
+        "a bidding heap needs at least two children per node"
+    );
+
+    pub fn with_arity() -> Self {
+        let () = Self::VALID_ARITY;
+        BiddingHeap {
+            bids: Vec::new(),
+            tombstones: Vec::new(),
+            tie_break: TieBreak::default(),
+            on_leader_change: None,
+        }
+    }
+
+    pub fn with_arity_and_tie_break(tie_break: TieBreak) -> Self {
+        let mut heap = BiddingHeap::with_arity();
+        heap.tie_break = tie_break;
+        heap
+    }
+
+    pub fn with_spill(threshold: usize, path: PathBuf) -> Result<SpillingHeap<D>, BidError> {
+        // the file at path is created or truncated, so stale contents never come back as bids
+        File::create(&path).map_err(|e| BidError::Io(e.to_string()))?;
+        Ok(SpillingHeap {
+            heap: BiddingHeap::with_arity(),
+            threshold,
+            path,
+            runs: Vec::new(),
+            spilled: 0,
+            file_len: 0,
+        })
+    }
+
+    // for decoders that carry tombstones alongside the bids
+    #[cfg(feature = "protobuf")]
+    pub(crate) fn from_parts(bids: Vec<Bid>, tombstones: Vec<Bid>, tie_break: TieBreak) -> Self {
+        BiddingHeap::from(HeapSnapshot {
+            bids,
+            tombstones,
+            tie_break,
+        })
+    }
+
+    pub fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+
+    pub fn len(&self) -> usize {
+        self.bids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bids.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.bids.reserve(additional);
+    }
+
+    #[cfg(feature = "bincode")]
+    pub fn to_bincode(&self) -> Result<Vec<u8>, BidError> {
+        // encoded as a snapshot, so tombstones and the tie break travel with the bids, as in HeapProto
+        bincode::serialize(&self.snapshot()).map_err(|e| BidError::Serialization(e.to_string()))
+    }
+
+    /// Returns every bid, active and removed, in heap order rather than sorted order.
+    pub fn as_slice(&self) -> &[Bid] {
+        &self.bids
+    }
+
+    pub fn as_mut_slice(&mut self) -> HeapGuard<'_, D> {
+        HeapGuard {
+            previous_leaders: self.leader_keys(),
+            heap: self,
+            dirty: false,
+        }
+    }
+
+    pub fn active_view(&self) -> ActiveView<'_> {
+        ActiveView { bids: &self.bids }
+    }
+
+    /// Returns copies of bids as they were just before removal, in removal order.
+    pub fn tombstones(&self) -> &[Bid] {
+        &self.tombstones
+    }
+
+    pub fn to_dot(&self) -> String {
+        // nodes are named by heap index, so each edge runs from (index - 1) / D to index
+        let mut dot = String::from("digraph bidding_heap {\n");
+        for (index, bid) in self.bids.iter().enumerate() {
+            dot.push_str(&format!(
+                "    {} [label=\"id: {}, amount: {}\"];\n",
+                index, bid.id, bid.amount
+            ));
+        }
+        for index in 1..self.bids.len() {
+            dot.push_str(&format!("    {} -> {};\n", (index - 1) / D, index));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn visit_level_order<F: FnMut(usize, &Bid)>(&self, mut f: F) {
+        // storage order is level order; level k holds up to D^k bids
+        let mut depth = 0;
+        let mut level_len = 1;
+        let mut level_end = 1;
+        for (index, bid) in self.bids.iter().enumerate() {
+            if index == level_end {
+                depth += 1;
+                level_len *= D;
+                level_end += level_len;
+            }
+            f(depth, bid);
+        }
+    }
+
+    pub fn push(&mut self, bid: Bid) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(
+            "bidding_heap.push",
+            auction_id = %bid.auction_id,
+            bid_id = bid.id
+        )
+        .entered();
+        #[cfg(feature = "logging")]
+        let id = bid.id;
+
+        let previous_leaders = self.leader_keys();
+        self.bids.push(bid);
+        let last = self.bids.len() - 1;
+        self.sift_up(last);
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!("pushed bid {}; heap length {}", id, self.bids.len());
+        #[cfg(feature = "tracing")]
+        tracing::trace!(heap_len = self.bids.len(), "pushed bid");
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!("bids_placed_total").increment(1);
+            metrics::histogram!("heap_size").record(self.bids.len() as f64);
+        }
+    }
+
+    pub fn push_rounded(&mut self, mut bid: Bid, tick: i32) -> Result<(), BidError> {
+        // rounds toward negative infinity, so negative amounts move away from zero
+        if tick <= 0 {
+            return Err(BidError::InvalidTick);
+        }
+        bid.amount = bid
+            .amount
+            .checked_sub(bid.amount.rem_euclid(tick))
+            .ok_or(BidError::Overflow)?;
+        self.push(bid);
+        Ok(())
+    }
+
+    pub fn push_with_bidder_cap(
+        &mut self,
+        bid: Bid,
+        max_per_bidder: usize,
+    ) -> Result<(), BidError> {
+        let active_bids_by_bidder = self
+            .bids
+            .iter()
+            .filter(|b| {
+                b.is_active() && b.auction_id == bid.auction_id && b.bidder_id == bid.bidder_id
+            })
+            .count();
+
+        if active_bids_by_bidder >= max_per_bidder {
+            return Err(BidError::BidderLimitReached);
+        }
+
+        self.push(bid);
+        Ok(())
+    }
+
+    pub fn push_with_rate_limit(
+        &mut self,
+        bid: Bid,
+        min_interval: Duration,
+    ) -> Result<(), BidError> {
+        let last_made_at = self
+            .bids
+            .iter()
+            .filter(|b| {
+                b.is_active() && b.auction_id == bid.auction_id && b.bidder_id == bid.bidder_id
+            })
+            .map(|b| b.made_at)
+            .max();
+
+        if let Some(last_made_at) = last_made_at {
+            if bid.made_at - last_made_at < min_interval {
+                return Err(BidError::RateLimited);
+            }
+        }
+
+        self.push(bid);
+        Ok(())
+    }
+
+    pub fn push_allocated(
+        &mut self,
+        auction_id: String,
+        bidder_id: String,
+        amount: i32,
+        allocator: &mut BidIdAllocator,
+    ) -> Result<i32, BidError> {
+        let id = allocator.allocate(&bidder_id)?;
+        self.push(Bid::new(auction_id, bidder_id, id, amount));
+        Ok(id)
+    }
+
+    pub fn push_if_leader(&mut self, bid: Bid) -> Result<bool, BidError> {
+        if !bid.is_active() {
+            return Err(BidError::BidInactive);
+        }
+
+        let outranks_leader = match self.leader(&bid.auction_id) {
+            Some(leader) => self.tie_break.is_higher_bid_than(&bid, leader),
+            None => true,
+        };
+
+        if outranks_leader {
+            self.push(bid);
+        }
+        Ok(outranks_leader)
+    }
+
+    pub fn push_idempotent(&mut self, bid: Bid) -> bool {
+        // a retried delivery may carry a different id, so the duplicate check ignores it
+        let is_duplicate = self.bids.iter().any(|b| {
+            b.auction_id == bid.auction_id
+                && b.bidder_id == bid.bidder_id
+                && b.amount == bid.amount
+                && b.made_at == bid.made_at
+        });
+
+        if !is_duplicate {
+            self.push(bid);
+        }
+        !is_duplicate
+    }
+
+    pub fn peek(&self) -> Option<&Bid> {
+        self.bids.first()
+    }
+
+    pub fn leader(&self, auction_id: &str) -> Option<&Bid> {
+        self.bids
+            .iter()
+            .filter(|bid| bid.is_active() && bid.auction_id == auction_id)
+            .fold(None, |leader: Option<&Bid>, bid| match leader {
+                Some(leader) if !self.tie_break.is_higher_bid_than(bid, leader) => Some(leader),
+                _ => Some(bid),
+            })
+    }
+
+    pub fn leader_diff(
+        &self,
+        other: &BiddingHeap<D>,
+    ) -> HashMap<String, (Option<i32>, Option<i32>)> {
+        // - covers every auction present in either heap, including removed-only ones
+        // - only auctions whose active leader ids differ are reported
+        let auction_ids: HashSet<&str> = self
+            .bids
+            .iter()
+            .chain(other.bids.iter())
+            .map(|bid| bid.auction_id.as_str())
+            .collect();
+        auction_ids
+            .into_iter()
+            .filter_map(|auction_id| {
+                let ours = self.leader(auction_id).map(|bid| bid.id);
+                let theirs = other.leader(auction_id).map(|bid| bid.id);
+                if ours == theirs {
+                    None
+                } else {
+                    Some((auction_id.to_string(), (ours, theirs)))
+                }
+            })
+            .collect()
+    }
+
+    pub fn leader_age(&self, auction_id: &str, now: DateTime<Utc>) -> Option<Duration> {
+        self.leader(auction_id).map(|leader| now - leader.made_at)
+    }
+
+    pub fn bid_velocity(&self, auction_id: &str, window: Duration, now: DateTime<Utc>) -> f64 {
+        // active bids made in (now - window, now], per minute of window; an empty window is 0.0
+        if window <= Duration::zero() {
+            return 0.0;
+        }
+        let since = now - window;
+        let count = self
+            .bids
+            .iter()
+            .filter(|bid| bid.is_active() && bid.auction_id == auction_id)
+            .filter(|bid| bid.made_at > since && bid.made_at <= now)
+            .count();
+        count as f64 / (window.num_milliseconds() as f64 / 60_000.0)
+    }
+
+    pub fn bottom_n_active(&self, n: usize) -> Vec<&Bid> {
+        let mut active_bids: Vec<&Bid> = self.bids.iter().filter(|bid| bid.is_active()).collect();
+        active_bids.sort_by(|a, b| self.tie_break.cmp_bids(a, b));
+        active_bids.truncate(n);
+        active_bids
+    }
+
+    pub fn total_active_amount(&self, auction_id: &str) -> i64 {
+        self.bids
+            .iter()
+            .filter(|bid| bid.is_active() && bid.auction_id == auction_id)
+            .map(|bid| i64::from(bid.amount))
+            .sum()
+    }
+
+    pub fn leaderboard_since(&self, auction_id: &str, since: DateTime<Utc>, n: usize) -> Vec<&Bid> {
+        let mut leaderboard = self.ranked_active_bids(auction_id);
+        leaderboard.retain(|bid| bid.made_at > since);
+        leaderboard.truncate(n);
+        leaderboard
+    }
+
+    /// Flags bidders who bid again within `window` of their own previous bid while already leading.
+    ///
+    /// This is a heuristic for shill bidding, not proof of it. Every bid in the auction, including
+    /// removed ones, is replayed in `made_at` order, and the leader at each point is the highest
+    /// amount seen so far, with the earlier bid keeping the lead on ties.
+    pub fn rapid_self_bids(&self, auction_id: &str, window: Duration) -> Vec<String> {
+        let mut bids: Vec<&Bid> = self
+            .bids
+            .iter()
+            .filter(|bid| bid.auction_id == auction_id)
+            .collect();
+        bids.sort_by_key(|bid| bid.made_at);
+
+        let mut flagged: Vec<String> = Vec::new();
+        let mut leader: Option<&Bid> = None;
+        let mut last_bid_by_leader: Option<&Bid> = None;
+        for bid in bids {
+            if let (Some(current_leader), Some(last_bid)) = (leader, last_bid_by_leader) {
+                let is_leading = current_leader.bidder_id == bid.bidder_id;
+                if is_leading
+                    && bid.made_at - last_bid.made_at <= window
+                    && !flagged.contains(&bid.bidder_id)
+                {
+                    flagged.push(bid.bidder_id.clone());
+                }
+            }
+
+            match leader {
+                Some(current_leader) if bid.amount <= current_leader.amount => {
+                    if current_leader.bidder_id == bid.bidder_id {
+                        last_bid_by_leader = Some(bid);
+                    }
+                }
+                _ => {
+                    leader = Some(bid);
+                    last_bid_by_leader = Some(bid);
+                }
+            }
+        }
+        flagged
+    }
+
+    pub fn winning_bids(&self, auction_id: &str, units: usize) -> Vec<&Bid> {
+        let mut winning_bids = self.ranked_active_bids(auction_id);
+        winning_bids.truncate(units);
+        winning_bids
+    }
+
+    pub fn clearing_price(&self, auction_id: &str, units: usize) -> Option<i32> {
+        self.winning_bids(auction_id, units)
+            .last()
+            .map(|bid| bid.amount)
+    }
+
+    pub fn leader_snapshot(&self, auction_id: &str) -> Option<Bid> {
+        self.leader(auction_id).cloned()
+    }
+
+    pub fn partition_by_amount(&self, pivot: i32) -> (Vec<&Bid>, Vec<&Bid>) {
+        self.bids
+            .iter()
+            .filter(|bid| bid.is_active())
+            .partition(|bid| bid.amount < pivot)
+    }
+
+    pub fn winner_with_reserve(&self, auction_id: &str, reserve: i32) -> Option<&Bid> {
+        self.leader(auction_id)
+            .filter(|leader| leader.amount >= reserve)
+    }
+
+    pub fn increment_gaps(&self, auction_id: &str) -> Vec<i32> {
+        let mut amounts: Vec<i32> = self
+            .bids
+            .iter()
+            .filter(|bid| bid.is_active() && bid.auction_id == auction_id)
+            .map(|bid| bid.amount)
+            .collect();
+        amounts.sort_unstable();
+        amounts
+            .windows(2)
+            .map(|pair| pair[1].saturating_sub(pair[0]))
+            .collect()
+    }
+
+    pub fn bids_per_bidder(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for bid in self.bids.iter().filter(|bid| bid.is_active()) {
+            *counts.entry(bid.bidder_id.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn activity_timeline(
+        &self,
+        auction_id: &str,
+        bucket: Duration,
+    ) -> Result<BTreeMap<DateTime<Utc>, usize>, BidError> {
+        // buckets are aligned to the unix epoch and count every bid placed, including removed ones
+        // - nanoseconds are counted in i128, since i64 ones run out in 2262
+        const NANOS_PER_SECOND: i128 = 1_000_000_000;
+        let bucket_nanos =
+            i128::from(bucket.num_seconds()) * NANOS_PER_SECOND + i128::from(bucket.subsec_nanos());
+        if bucket_nanos <= 0 {
+            return Err(BidError::InvalidBucket);
+        }
+
+        let mut timeline = BTreeMap::new();
+        for bid in self.bids.iter().filter(|bid| bid.auction_id == auction_id) {
+            let made_at_nanos = i128::from(bid.made_at.timestamp()) * NANOS_PER_SECOND
+                + i128::from(bid.made_at.timestamp_subsec_nanos());
+            let start_nanos = made_at_nanos - made_at_nanos.rem_euclid(bucket_nanos);
+            let start = i64::try_from(start_nanos.div_euclid(NANOS_PER_SECOND))
+                .ok()
+                .and_then(|seconds| {
+                    DateTime::from_timestamp(
+                        seconds,
+                        start_nanos.rem_euclid(NANOS_PER_SECOND) as u32,
+                    )
+                })
+                .ok_or(BidError::InvalidBucket)?;
+            *timeline.entry(start).or_insert(0) += 1;
+        }
+        Ok(timeline)
+    }
+
+    pub fn preview_rank(&self, bid: &Bid) -> usize {
+        // zero-based among active bids in the same auction; an equivalent bid already placed keeps
+        // its place ahead of the prospective one
+        self.bids
+            .iter()
+            .filter(|b| b.is_active() && b.auction_id == bid.auction_id)
+            .filter(|b| !self.tie_break.is_higher_bid_than(bid, b))
+            .count()
+    }
+
+    pub fn median_bid(&self) -> Option<&Bid> {
+        // ranked lowest first, so the lower middle of an even count is at (len - 1) / 2
+        let mut active_bids: Vec<&Bid> = self.bids.iter().filter(|bid| bid.is_active()).collect();
+        if active_bids.is_empty() {
+            return None;
+        }
+        active_bids.sort_by(|a, b| self.tie_break.cmp_bids(a, b));
+        Some(active_bids[(active_bids.len() - 1) / 2])
+    }
+
+    pub fn fair_leader(&self, auction_id: &str, seed: u64) -> Option<&Bid> {
+        // every active bid on the top amount is eligible, whatever its time; the pick is the
+        // lowest fnv1a_64 of (seed, bidder_id), so a seed picks the same bid on every toolchain
+        let top_amount = self.leader(auction_id)?.amount;
+        self.bids
+            .iter()
+            .filter(|bid| {
+                bid.is_active() && bid.auction_id == auction_id && bid.amount == top_amount
+            })
+            .min_by_key(|bid| {
+                let mut bytes = seed.to_be_bytes().to_vec();
+                bytes.extend_from_slice(bid.bidder_id.as_bytes());
+                (fnv1a_64(&bytes), bid.id)
+            })
+    }
+
+    pub fn full_ranking(&self, auction_id: &str) -> Vec<(usize, &Bid)> {
+        // equivalent bids share a rank and the next rank skips past them, e.g. 1, 1, 3
+        let mut ranking: Vec<(usize, &Bid)> = Vec::new();
+        for (index, bid) in self.ranked_active_bids(auction_id).into_iter().enumerate() {
+            let rank = match ranking.last() {
+                Some((rank, previous)) if bid.is_equivalent_bid_to(previous) => *rank,
+                _ => index + 1,
+            };
+            ranking.push((rank, bid));
+        }
+        ranking
+    }
+
+    pub fn amount_to_lead(&self, auction_id: &str, bidder_id: &str, increment: i32) -> Option<i32> {
+        // the new bid must reach the leader's amount plus increment, as in Bid::can_outbid
+        // - None when there is no leader, the bidder has no active bid, or the gap overflows i32
+        let leader = self.leader(auction_id)?;
+        if leader.bidder_id == bidder_id {
+            return Some(0);
+        }
+        let highest = self
+            .bids
+            .iter()
+            .filter(|bid| {
+                bid.is_active() && bid.auction_id == auction_id && bid.bidder_id == bidder_id
+            })
+            .map(|bid| bid.amount)
+            .max()?;
+
+        let gap = i64::from(leader.amount) + i64::from(increment) - i64::from(highest);
+        i32::try_from(gap).ok()
+    }
+
+    pub fn to_columns(&self) -> BidColumns {
+        // active bids only, highest first
+        let mut active_bids: Vec<&Bid> = self.bids.iter().filter(|bid| bid.is_active()).collect();
+        active_bids.sort_by(|a, b| self.tie_break.cmp_bids(b, a));
+
+        let mut columns = BidColumns::default();
+        for bid in active_bids {
+            columns.push(bid);
+        }
+        columns
+    }
+
+    /// Estimates the chance that a bid of `amount` wins `auction_id` as the fraction of active bids
+    /// in that auction with a strictly lower amount.
+    ///
+    /// The model assumes the competing bids already placed are a fair sample of what rivals will
+    /// bid, and ignores time tie-breaks, increments and any bids still to come. An auction without
+    /// active bids gives 1.0, since there is nothing to beat.
+    pub fn estimated_win_probability(&self, auction_id: &str, amount: i32) -> f64 {
+        let amounts: Vec<i32> = self
+            .bids
+            .iter()
+            .filter(|bid| bid.is_active() && bid.auction_id == auction_id)
+            .map(|bid| bid.amount)
+            .collect();
+        if amounts.is_empty() {
+            return 1.0;
+        }
+        let lower = amounts
+            .iter()
+            .filter(|bid_amount| **bid_amount < amount)
+            .count();
+        lower as f64 / amounts.len() as f64
+    }
+
+    pub fn would_be_outbid_by(&self, auction_id: &str, amount: i32) -> Vec<&Bid> {
+        // highest first, so the bidders about to lose the most come first
+        self.ranked_active_bids(auction_id)
+            .into_iter()
+            .filter(|bid| bid.amount < amount)
+            .collect()
+    }
+
+    pub fn entry_floor(&self, auction_id: &str, reserve: i32, increment: i32) -> i32 {
+        // saturates rather than overflowing for a leader near i32::MAX
+        match self.leader(auction_id) {
+            Some(leader) => reserve.max(leader.amount.saturating_add(increment)),
+            None => reserve,
+        }
+    }
+
+    pub fn bids_without_auction(&self, known_auctions: &HashSet<String>) -> Vec<&Bid> {
+        // removed bids are included, since an orphan is a data problem whatever its state
+        self.bids
+            .iter()
+            .filter(|bid| !known_auctions.contains(&bid.auction_id))
+            .collect()
+    }
+
+    pub fn pop(&mut self) -> Option<Bid> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!("bidding_heap.pop", auction_id = tracing::field::Empty);
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        if self.bids.is_empty() {
+            #[cfg(feature = "logging")]
+            log::trace!("pop on empty heap");
+            return None;
+        }
+
+        // swap the root out to the end so Vec::pop truncates it, then restore the heap from the root
+        let previous_leaders = self.leader_keys();
+        let last = self.bids.len() - 1;
+        self.bids.swap(0, last);
+        let bid = self.bids.pop();
+        self.sift_down(0);
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        if let Some(bid) = &bid {
+            log::debug!("popped bid {}; heap length {}", bid.id, self.bids.len());
+        }
+        #[cfg(feature = "tracing")]
+        if let Some(bid) = &bid {
+            span.record("auction_id", tracing::field::display(&bid.auction_id));
+            tracing::trace!(bid_id = bid.id, heap_len = self.bids.len(), "popped bid");
+        }
+        #[cfg(feature = "metrics")]
+        metrics::histogram!("heap_size").record(self.bids.len() as f64);
+        bid
+    }
+
+    pub fn remove(&mut self, id: i32) -> bool {
+        self.remove_by_id(id, None)
+    }
+
+    pub fn remove_with_reason(&mut self, id: i32, reason: RemovalReason) -> bool {
+        self.remove_by_id(id, Some(reason))
+    }
+
+    pub fn retract_within_grace(
+        &mut self,
+        id: i32,
+        grace: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<(), BidError> {
+        // a retraction is recorded as a withdrawal
+        let index = self.active_position(id).ok_or(BidError::BidNotFound)?;
+        if now - self.bids[index].made_at > grace {
+            return Err(BidError::GraceExpired);
+        }
+        self.remove_with_reason(id, RemovalReason::Withdrawn);
+        Ok(())
+    }
+
+    pub fn removed_by_reason(&self, reason: RemovalReason) -> Vec<&Bid> {
+        self.bids
+            .iter()
+            .filter(|bid| !bid.is_active() && bid.removal_reason == Some(reason))
+            .collect()
+    }
+
+    fn remove_by_id(&mut self, id: i32, reason: Option<RemovalReason>) -> bool {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "bidding_heap.remove",
+            auction_id = tracing::field::Empty,
+            bid_id = id
+        );
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        let index = match self
+            .bids
+            .iter()
+            .position(|bid| bid.is_active() && bid.id == id)
+        {
+            Some(index) => index,
+            None => {
+                #[cfg(feature = "logging")]
+                log::trace!("no active bid {} to remove", id);
+                return false;
+            }
+        };
+
+        // a removed bid only ever ranks lower, so it can only need to move down
+        let previous_leaders = self.leader_keys();
+        self.tombstones.push(self.bids[index].clone());
+        match reason {
+            Some(reason) => self.bids[index].remove_with_reason(reason),
+            None => self.bids[index].remove(),
+        }
+        #[cfg(feature = "tracing")]
+        span.record(
+            "auction_id",
+            tracing::field::display(&self.bids[index].auction_id),
+        );
+        self.sift_down(index);
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!("removed bid {}; heap length {}", id, self.bids.len());
+        #[cfg(feature = "tracing")]
+        tracing::trace!(heap_len = self.bids.len(), "removed bid");
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!("bids_removed_total").increment(1);
+            metrics::histogram!("heap_size").record(self.bids.len() as f64);
+        }
+        true
+    }
+
+    pub fn trim_lowest(&mut self) -> Option<Bid> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "bidding_heap.trim_lowest",
+            auction_id = tracing::field::Empty,
+            bid_id = tracing::field::Empty
+        );
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        let index = self
+            .bids
+            .iter()
+            .enumerate()
+            .filter(|(_, bid)| bid.is_active())
+            .min_by(|(_, a), (_, b)| self.tie_break.cmp_bids(a, b))
+            .map(|(index, _)| index)?;
+
+        let previous_leaders = self.leader_keys();
+        self.tombstones.push(self.bids[index].clone());
+        self.bids[index].remove();
+        let removed = self.bids[index].clone();
+        self.sift_down(index);
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!(
+            "trimmed bid {}; heap length {}",
+            removed.id,
+            self.bids.len()
+        );
+        #[cfg(feature = "tracing")]
+        {
+            span.record("auction_id", tracing::field::display(&removed.auction_id));
+            span.record("bid_id", removed.id);
+            tracing::trace!(heap_len = self.bids.len(), "trimmed bid");
+        }
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!("bids_removed_total").increment(1);
+            metrics::histogram!("heap_size").record(self.bids.len() as f64);
+        }
+        Some(removed)
+    }
+
+    pub fn increase_amount(&mut self, id: i32, delta: u32) -> Result<bool, BidError> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "bidding_heap.increase_amount",
+            auction_id = tracing::field::Empty,
+            bid_id = id,
+            delta
+        );
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        // a higher amount can only move a bid towards the root
+        let index = match self.active_position(id) {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+        let amount = self.bids[index]
+            .amount
+            .checked_add_unsigned(delta)
+            .ok_or(BidError::Overflow)?;
+
+        #[cfg(feature = "tracing")]
+        span.record(
+            "auction_id",
+            tracing::field::display(&self.bids[index].auction_id),
+        );
+        let previous_leaders = self.leader_keys();
+        self.bids[index].amount = amount;
+        self.sift_up(index);
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!("increased bid {} by {} to {}", id, delta, amount);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(amount, "increased bid amount");
+        Ok(true)
+    }
+
+    pub fn decrease_amount(&mut self, id: i32, delta: u32) -> Result<bool, BidError> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "bidding_heap.decrease_amount",
+            auction_id = tracing::field::Empty,
+            bid_id = id,
+            delta
+        );
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        // a lower amount can only move a bid towards the leaves
+        let index = match self.active_position(id) {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+        let amount = self.bids[index]
+            .amount
+            .checked_sub_unsigned(delta)
+            .ok_or(BidError::Overflow)?;
+
+        #[cfg(feature = "tracing")]
+        span.record(
+            "auction_id",
+            tracing::field::display(&self.bids[index].auction_id),
+        );
+        let previous_leaders = self.leader_keys();
+        self.bids[index].amount = amount;
+        self.sift_down(index);
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!("decreased bid {} by {} to {}", id, delta, amount);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(amount, "decreased bid amount");
+        Ok(true)
+    }
+
+    fn active_position(&self, id: i32) -> Option<usize> {
+        self.bids
+            .iter()
+            .position(|bid| bid.is_active() && bid.id == id)
+    }
+
+    pub fn remove_ids(&mut self, ids: &[i32]) -> usize {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("bidding_heap.remove_ids", ids = ids.len()).entered();
+
+        self.remove_where(|bid| ids.contains(&bid.id)).len()
+    }
+
+    pub fn remove_where<F: Fn(&Bid) -> bool>(&mut self, f: F) -> Vec<Bid> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("bidding_heap.remove_where").entered();
+
+        let previous_leaders = self.leader_keys();
+        let mut removed = Vec::new();
+        for bid in self.bids.iter_mut() {
+            if bid.is_active() && f(bid) {
+                self.tombstones.push(bid.clone());
+                bid.remove();
+                removed.push(bid.clone());
+            }
+        }
+
+        if !removed.is_empty() {
+            self.heapify();
+        }
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!(
+            "removed {} matching bids; heap length {}",
+            removed.len(),
+            self.bids.len()
+        );
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            removed = removed.len(),
+            heap_len = self.bids.len(),
+            "removed bids"
+        );
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!("bids_removed_total").increment(removed.len() as u64);
+            metrics::histogram!("heap_size").record(self.bids.len() as f64);
+        }
+        removed
+    }
+
+    pub fn collapse_bidder_bids(&mut self, auction_id: &str) -> usize {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!("bidding_heap.collapse_bidder_bids", auction_id = %auction_id)
+                .entered();
+
+        let previous_leaders = self.leader_keys();
+        let mut highest_by_bidder: HashMap<&str, usize> = HashMap::new();
+        for (index, bid) in self.bids.iter().enumerate() {
+            if !bid.is_active() || bid.auction_id != auction_id {
+                continue;
+            }
+            let highest = highest_by_bidder.entry(&bid.bidder_id).or_insert(index);
+            if self.tie_break.is_higher_bid_than(bid, &self.bids[*highest]) {
+                *highest = index;
+            }
+        }
+        let kept: HashSet<usize> = highest_by_bidder.into_values().collect();
+
+        let mut removed = 0;
+        for (index, bid) in self.bids.iter_mut().enumerate() {
+            if bid.is_active() && bid.auction_id == auction_id && !kept.contains(&index) {
+                self.tombstones.push(bid.clone());
+                bid.remove();
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            self.heapify();
+        }
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!("collapsed {} bids in auction {}", removed, auction_id);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(removed, "collapsed bidder bids");
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!("bids_removed_total").increment(removed as u64);
+            metrics::histogram!("heap_size").record(self.bids.len() as f64);
+        }
+        removed
+    }
+
+    pub fn reassign_ids(&mut self, start: i32) -> Result<i32, BidError> {
+        // ids never take part in ordering, so renumbering in storage order keeps the heap valid
+        // - tombstones keep the ids the bids had when they were removed
+        // - the returned next id must fit too; nothing is renumbered when it doesn't
+        // - every auction keeps the same leading bid, so on_leader_change is not notified
+        let next_id = i32::try_from(self.bids.len())
+            .ok()
+            .and_then(|len| start.checked_add(len))
+            .ok_or(BidError::IdOverflow)?;
+        for (id, bid) in (start..next_id).zip(self.bids.iter_mut()) {
+            bid.id = id;
+        }
+        Ok(next_id)
+    }
+
+    pub fn drain_sorted(self) -> Vec<Bid> {
+        self.drain_map(|bid| bid)
+    }
+
+    pub fn drain_sorted_by<F: FnMut(&Bid, &Bid) -> Ordering>(mut self, mut compare: F) -> Vec<Bid> {
+        let mut is_higher = |a: &Bid, b: &Bid| compare(a, b) == Ordering::Greater;
+        heapify_by::<D, _>(&mut self.bids, &mut is_higher);
+
+        let mut drained = Vec::with_capacity(self.bids.len());
+        while !self.bids.is_empty() {
+            let last = self.bids.len() - 1;
+            self.bids.swap(0, last);
+            drained.extend(self.bids.pop());
+            sift_down_by::<D, _>(&mut self.bids, 0, &mut is_higher);
+        }
+        drained
+    }
+
+    pub fn write_ndjson(self, mut writer: impl Write) -> Result<(), BidError> {
+        for bid in self {
+            serde_json::to_writer(&mut writer, &bid)
+                .map_err(|e| BidError::Serialization(e.to_string()))?;
+            writer
+                .write_all(b"\n")
+                .map_err(|e| BidError::Io(e.to_string()))?;
+        }
+        writer.flush().map_err(|e| BidError::Io(e.to_string()))
+    }
+
+    pub fn drain_map<T, F: FnMut(Bid) -> T>(mut self, mut f: F) -> Vec<T> {
+        let mut drained = Vec::with_capacity(self.bids.len());
+        while let Some(bid) = self.pop() {
+            drained.push(f(bid));
+        }
+        drained
+    }
+
+    pub fn scale_amounts(&mut self, factor: i32) -> Result<(), BidError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("bidding_heap.scale_amounts", factor).entered();
+
+        let scaled_amounts = self
+            .bids
+            .iter()
+            .map(|bid| bid.amount.checked_mul(factor).ok_or(BidError::Overflow))
+            .collect::<Result<Vec<i32>, BidError>>()?;
+
+        let previous_leaders = self.leader_keys();
+        for (bid, amount) in self.bids.iter_mut().zip(scaled_amounts) {
+            bid.amount = amount;
+        }
+        self.heapify();
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "logging")]
+        log::debug!("scaled {} bid amounts by {}", self.bids.len(), factor);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(heap_len = self.bids.len(), "scaled bid amounts");
+        Ok(())
+    }
+
+    pub fn compact(&mut self) -> usize {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("bidding_heap.compact").entered();
+
+        let len = self.bids.len();
+        self.bids.retain(|bid| bid.is_active());
+        self.heapify();
+        let compacted = len - self.bids.len();
+
+        #[cfg(feature = "logging")]
+        log::debug!(
+            "compacted {} removed bids; heap length {}",
+            compacted,
+            self.bids.len()
+        );
+        #[cfg(feature = "tracing")]
+        tracing::trace!(compacted, heap_len = self.bids.len(), "compacted heap");
+        #[cfg(feature = "metrics")]
+        metrics::histogram!("heap_size").record(self.bids.len() as f64);
+        compacted
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (1..self.bids.len()).all(|index| {
+            let parent = (index - 1) / D;
+            !self
+                .tie_break
+                .is_higher_bid_than(&self.bids[index], &self.bids[parent])
+        })
+    }
+
+    pub fn validate_index(&self) -> bool {
+        // there is no id->index map, so this checks the invariants the storage itself relies on
+        // - the heap property, as is_valid checks it
+        // - every bid is identified by a unique (auction_id, id), as diff_since and apply_diff assume
+        let mut keys: HashSet<(&str, i32)> = HashSet::with_capacity(self.bids.len());
+        self.is_valid()
+            && self
+                .bids
+                .iter()
+                .all(|bid| keys.insert((bid.auction_id.as_str(), bid.id)))
+    }
+
+    pub fn snapshot(&self) -> HeapSnapshot {
+        HeapSnapshot {
+            bids: self.bids.clone(),
+            tombstones: self.tombstones.clone(),
+            tie_break: self.tie_break,
+        }
+    }
+
+    pub fn diff_since(&self, checkpoint: &HeapSnapshot) -> HeapDiff {
+        // assumes (auction_id, id) is unique within the heap and within the checkpoint
+        let before: HashMap<(&str, i32), &Bid> = checkpoint
+            .bids
+            .iter()
+            .map(|bid| ((bid.auction_id.as_str(), bid.id), bid))
+            .collect();
+        let mut diff = HeapDiff::default();
+        let mut seen: HashSet<(&str, i32)> = HashSet::with_capacity(self.bids.len());
+        for bid in &self.bids {
+            let key = (bid.auction_id.as_str(), bid.id);
+            seen.insert(key);
+            match before.get(&key) {
+                None => diff.added.push(bid.clone()),
+                Some(previous) if *previous != bid => diff.updated.push(bid.clone()),
+                Some(_) => {}
+            }
+        }
+        for bid in &checkpoint.bids {
+            if !seen.contains(&(bid.auction_id.as_str(), bid.id)) {
+                diff.removed.push((bid.auction_id.clone(), bid.id));
+            }
+        }
+        diff
+    }
+
+    pub fn apply_diff(&mut self, diff: &HeapDiff) {
+        let previous_leaders = self.leader_keys();
+        let removed: HashSet<(&str, i32)> = diff
+            .removed
+            .iter()
+            .map(|(auction_id, id)| (auction_id.as_str(), *id))
+            .collect();
+        let updated: HashMap<(&str, i32), &Bid> = diff
+            .updated
+            .iter()
+            .map(|bid| ((bid.auction_id.as_str(), bid.id), bid))
+            .collect();
+        self.bids
+            .retain(|bid| !removed.contains(&(bid.auction_id.as_str(), bid.id)));
+        for bid in self.bids.iter_mut() {
+            if let Some(updated) = updated.get(&(bid.auction_id.as_str(), bid.id)) {
+                *bid = (*updated).clone();
+            }
+        }
+        self.bids.extend(diff.added.iter().cloned());
+        self.heapify();
+        self.notify_leader_change(previous_leaders);
+
+        #[cfg(feature = "metrics")]
+        metrics::histogram!("heap_size").record(self.bids.len() as f64);
+    }
+
+    pub fn heal(&mut self) -> bool {
+        // a safety net for bids mutated behind the heap's back; a valid heap is left untouched
+        if self.is_valid() {
+            return false;
+        }
+        self.heapify();
+        true
+    }
+
+    pub fn on_leader_change<F: FnMut(&Bid) + 'static>(&mut self, callback: F) {
+        self.on_leader_change = Some(Box::new(callback));
+    }
+
+    // leaders are tracked per auction, as leader(auction_id) reports them
+    // - mutators take leader_keys before changing anything and pass them to notify_leader_change
+    // - the callback runs once for each auction whose active leader is a different bid afterwards,
+    //   in auction_id order; an auction left without active bids is not reported
+    // - both are O(n) when a callback is registered and free otherwise
+
+    fn leader_keys(&self) -> Option<BTreeMap<String, i32>> {
+        self.on_leader_change.as_ref()?;
+        Some(
+            self.leaders()
+                .into_iter()
+                .map(|(auction_id, bid)| (auction_id.to_string(), bid.id))
+                .collect(),
+        )
+    }
+
+    fn notify_leader_change(&mut self, previous_leaders: Option<BTreeMap<String, i32>>) {
+        let previous_leaders = match previous_leaders {
+            Some(previous_leaders) => previous_leaders,
+            None => return,
+        };
+        let changed: Vec<Bid> = self
+            .leaders()
+            .into_iter()
+            .filter(|(auction_id, bid)| previous_leaders.get(*auction_id) != Some(&bid.id))
+            .map(|(_, bid)| bid.clone())
+            .collect();
+        if let Some(callback) = self.on_leader_change.as_mut() {
+            for bid in changed {
+                callback(&bid);
+            }
+        }
+    }
+
+    fn leaders(&self) -> BTreeMap<&str, &Bid> {
+        // the same per-auction leaders as leader
+        let mut leaders: BTreeMap<&str, &Bid> = BTreeMap::new();
+        for bid in self.bids.iter().filter(|bid| bid.is_active()) {
+            let leader = leaders.entry(&bid.auction_id).or_insert(bid);
+            if self.tie_break.is_higher_bid_than(bid, leader) {
+                *leader = bid;
+            }
+        }
+        leaders
+    }
+
+    fn ranked_active_bids(&self, auction_id: &str) -> Vec<&Bid> {
+        let mut ranked_bids: Vec<&Bid> = self
+            .bids
+            .iter()
+            .filter(|bid| bid.is_active() && bid.auction_id == auction_id)
+            .collect();
+        ranked_bids.sort_by(|a, b| self.tie_break.cmp_bids(b, a));
+        ranked_bids
+    }
+
+    fn heapify(&mut self) {
+        let tie_break = self.tie_break;
+        heapify_by::<D, _>(&mut self.bids, &mut |a, b| {
+            tie_break.is_higher_bid_than(a, b)
+        });
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / D;
+            if !self
+                .tie_break
+                .is_higher_bid_than(&self.bids[index], &self.bids[parent])
+            {
+                break;
+            }
+            self.bids.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, index: usize) {
+        let tie_break = self.tie_break;
+        sift_down_by::<D, _>(&mut self.bids, index, &mut |a, b| {
+            tie_break.is_higher_bid_than(a, b)
+        });
+    }
+}
+
+fn heapify_by<const D: usize, F: FnMut(&Bid, &Bid) -> bool>(bids: &mut [Bid], is_higher: &mut F) {
+    // every index up to the parent of the last bid has children
+    let parents = (bids.len() + D - 2) / D;
+    for index in (0..parents).rev() {
+        sift_down_by::<D, _>(bids, index, is_higher);
+    }
+}
+
+fn sift_down_by<const D: usize, F: FnMut(&Bid, &Bid) -> bool>(
+    bids: &mut [Bid],
+    mut index: usize,
+    is_higher: &mut F,
+) {
+    let len = bids.len();
+    loop {
+        let first_child = D * index + 1;
+        let mut highest = index;
+
+        for child in first_child..len.min(first_child + D) {
+            if is_higher(&bids[child], &bids[highest]) {
+                highest = child;
+            }
+        }
+        if highest == index {
+            break;
+        }
+
+        bids.swap(index, highest);
+        index = highest;
+    }
+}
+
+impl<const D: usize> From<Vec<Bid>> for BiddingHeap<D> {
+    fn from(bids: Vec<Bid>) -> Self {
+        let mut heap = BiddingHeap::with_arity();
+        heap.bids = bids;
+        heap.heapify();
+        heap
+    }
+}
+
+impl<const D: usize> From<HeapSnapshot> for BiddingHeap<D> {
+    fn from(snapshot: HeapSnapshot) -> Self {
+        let mut heap = BiddingHeap::with_arity_and_tie_break(snapshot.tie_break);
+        heap.bids = snapshot.bids;
+        heap.tombstones = snapshot.tombstones;
+        heap.heapify();
+        heap
+    }
+}
+
+impl<const D: usize> From<BiddingHeap<D>> for Vec<Bid> {
+    // drains in sorted order, highest first
+    fn from(heap: BiddingHeap<D>) -> Self {
+        heap.drain_sorted()
+    }
+}
+
+pub struct IntoIter<const D: usize = 2> {
+    heap: BiddingHeap<D>,
+}
+
+impl<const D: usize> Iterator for IntoIter<D> {
+    type Item = Bid;
+
+    fn next(&mut self) -> Option<Bid> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<const D: usize> IntoIterator for BiddingHeap<D> {
+    // by value, bids come out in sorted order, highest first
+    type Item = Bid;
+    type IntoIter = IntoIter<D>;
+
+    fn into_iter(self) -> IntoIter<D> {
+        IntoIter { heap: self }
+    }
+}
+
+impl<'a, const D: usize> IntoIterator for &'a BiddingHeap<D> {
+    // by reference, bids come out in heap order
+    type Item = &'a Bid;
+    type IntoIter = std::slice::Iter<'a, Bid>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.bids.iter()
+    }
+}
+
+// a heap that keeps at most threshold bids in memory and moves the rest to an NDJSON file
+// - every method that can touch the file returns a Result; there is no in-place access to the
+//   bids, since spilled ones could not be seen, so into_heap is the way to the full BiddingHeap API
+// - once memory holds more than threshold bids, all but the highest threshold / 2 are sorted
+//   and appended to the file as one run, highest first
+// - each run's highest unread bid stays in memory, so peek and pop compare against the spill
+//   without reading it, and a reload merges at most threshold of the highest spilled bids back
+// - the file is truncated once every run has been read back
+#[derive(Debug)]
+pub struct SpillingHeap<const D: usize = 2> {
+    heap: BiddingHeap<D>,
+    threshold: usize,
+    path: PathBuf,
+    runs: Vec<SpillRun>,
+    spilled: usize,
+    file_len: u64,
+}
+
+// the unread part of one run: head, then remaining bids starting at offset in the file
+#[derive(Debug)]
+struct SpillRun {
+    head: Bid,
+    offset: u64,
+    remaining: usize,
+}
+
+impl<const D: usize> SpillingHeap<D> {
+    pub fn len(&self) -> usize {
+        self.heap.len() + self.spilled
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn spilled_len(&self) -> usize {
+        self.spilled
+    }
+
+    pub fn peek(&self) -> Option<&Bid> {
+        let head = self.highest_run().map(|index| &self.runs[index].head);
+        match (self.heap.peek(), head) {
+            (Some(root), Some(head)) if self.heap.tie_break.is_higher_bid_than(head, root) => {
+                Some(head)
+            }
+            (None, head) => head,
+            (root, _) => root,
+        }
+    }
+
+    pub fn push(&mut self, bid: Bid) -> Result<(), BidError> {
+        self.heap.push(bid);
+        if self.heap.bids.len() > self.threshold {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Result<Option<Bid>, BidError> {
+        // the spill is read back whenever its highest bid outranks the in-memory root
+        let spill_outranks_root = match (self.highest_run(), self.heap.peek()) {
+            (Some(index), Some(root)) => self
+                .heap
+                .tie_break
+                .is_higher_bid_than(&self.runs[index].head, root),
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if spill_outranks_root {
+            // room for at least one bid, so a heap full of low bids still makes progress
+            let room = self.threshold.saturating_sub(self.heap.bids.len()).max(1);
+            self.reload(room)?;
+        }
+        Ok(self.heap.pop())
+    }
+
+    pub fn drain_sorted(mut self) -> Result<Vec<Bid>, BidError> {
+        let mut drained = Vec::with_capacity(self.len());
+        while let Some(bid) = self.pop()? {
+            drained.push(bid);
+        }
+        Ok(drained)
+    }
+
+    pub fn into_heap(mut self) -> Result<BiddingHeap<D>, BidError> {
+        // reads every spilled bid back, so memory is no longer bounded by threshold
+        while !self.runs.is_empty() {
+            self.reload(self.spilled)?;
+        }
+        Ok(self.heap)
+    }
+
+    fn highest_run(&self) -> Option<usize> {
+        let tie_break = self.heap.tie_break;
+        self.runs
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| tie_break.cmp_bids(&a.head, &b.head))
+            .map(|(index, _)| index)
+    }
+
+    fn spill(&mut self) -> Result<(), BidError> {
+        // one selection and one sort per spill, rather than a scan for every evicted bid
+        let tie_break = self.heap.tie_break;
+        let keep = self.threshold / 2;
+        let bids = &mut self.heap.bids;
+        bids.select_nth_unstable_by(keep, |a, b| tie_break.cmp_bids(b, a));
+        bids[keep..].sort_unstable_by(|a, b| tie_break.cmp_bids(b, a));
+
+        // the run is written before any bid leaves memory, so a failed write loses nothing
+        let written = self.write_run(&self.heap.bids[keep + 1..]);
+        if let Ok(written) = written {
+            let mut evicted = self.heap.bids.split_off(keep).into_iter();
+            let head = evicted
+                .next()
+                .expect("more than threshold bids were in memory");
+            self.runs.push(SpillRun {
+                head,
+                offset: self.file_len,
+                remaining: evicted.len(),
+            });
+            self.spilled += evicted.len() + 1;
+            self.file_len += written;
+        }
+        self.heap.heapify();
+        written.map(|_| ())
+    }
+
+    fn write_run(&self, bids: &[Bid]) -> Result<u64, BidError> {
+        // written at file_len rather than appended, so bytes left by a failed write are overwritten
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .map_err(|e| BidError::Io(e.to_string()))?;
+        file.seek(SeekFrom::Start(self.file_len))
+            .map_err(|e| BidError::Io(e.to_string()))?;
+        let mut writer = BufWriter::new(file);
+        let mut written = 0;
+        for bid in bids {
+            let mut line =
+                serde_json::to_vec(bid).map_err(|e| BidError::Serialization(e.to_string()))?;
+            line.push(b'\n');
+            writer
+                .write_all(&line)
+                .map_err(|e| BidError::Io(e.to_string()))?;
+            written += line.len() as u64;
+        }
+        writer.flush().map_err(|e| BidError::Io(e.to_string()))?;
+        Ok(written)
+    }
+
+    fn reload(&mut self, limit: usize) -> Result<(), BidError> {
+        // merges up to limit of the highest spilled bids back, taking each run's head in turn
+        let mut reader: Option<BufReader<File>> = None;
+        let mut position = 0;
+        for _ in 0..limit {
+            let index = match self.highest_run() {
+                Some(index) => index,
+                None => break,
+            };
+
+            // the next head is read before the current one moves, so an error leaves the run intact
+            let run = &self.runs[index];
+            let next = if run.remaining > 0 {
+                let reader = match reader.as_mut() {
+                    Some(reader) => reader,
+                    None => {
+                        let file =
+                            File::open(&self.path).map_err(|e| BidError::Io(e.to_string()))?;
+                        position = 0;
+                        reader.insert(BufReader::new(file))
+                    }
+                };
+                if position != run.offset {
+                    reader
+                        .seek(SeekFrom::Start(run.offset))
+                        .map_err(|e| BidError::Io(e.to_string()))?;
+                }
+                let mut line = String::new();
+                let read = reader
+                    .read_line(&mut line)
+                    .map_err(|e| BidError::Io(e.to_string()))? as u64;
+                position = run.offset + read;
+                Some((Bid::from_json(line.trim_end())?, position))
+            } else {
+                None
+            };
+
+            let bid = match next {
+                Some((head, offset)) => {
+                    let run = &mut self.runs[index];
+                    run.offset = offset;
+                    run.remaining -= 1;
+                    std::mem::replace(&mut run.head, head)
+                }
+                None => self.runs.swap_remove(index).head,
+            };
+            self.spilled -= 1;
+            self.heap.bids.push(bid);
+            let last = self.heap.bids.len() - 1;
+            self.heap.sift_up(last);
+        }
+
+        if self.runs.is_empty() && self.file_len > 0 {
+            File::create(&self.path).map_err(|e| BidError::Io(e.to_string()))?;
+            self.file_len = 0;
+        }
+        Ok(())
+    }
+}
+
+pub struct CachedComparator<F> {
+    compare: F,
+    cache: HashMap<(BidKey, BidKey), Ordering>,
+}
+
+// ids are only unique within an auction, so a bid is identified by (auction_id, id)
+type BidKey = (String, i32);
+
+impl<F: FnMut(&Bid, &Bid) -> Ordering> CachedComparator<F> {
+    // memoizes an expensive comparator for the length of one operation
+    // - results are keyed by (auction_id, id) pairs, so those must be unique among the bids compared
+    // - the comparator must be antisymmetric, since a cached (a, b) result also answers (b, a)
+
+    pub fn new(compare: F) -> Self {
+        CachedComparator {
+            compare,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn compare(&mut self, a: &Bid, b: &Bid) -> Ordering {
+        let a_key: BidKey = (a.auction_id.clone(), a.id);
+        let b_key: BidKey = (b.auction_id.clone(), b.id);
+        if let Some(ordering) = self.cache.get(&(a_key.clone(), b_key.clone())) {
+            return *ordering;
+        }
+        if let Some(ordering) = self.cache.get(&(b_key.clone(), a_key.clone())) {
+            return ordering.reverse();
+        }
+
+        let ordering = (self.compare)(a, b);
+        self.cache.insert((a_key, b_key), ordering);
+        ordering
+    }
+}
+
+pub struct ActiveView<'a> {
+    bids: &'a [Bid],
+}
+
+impl<'a> ActiveView<'a> {
+    // removed bids always sink below active ones, so an active root is the active leader
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    pub fn peek(&self) -> Option<&'a Bid> {
+        self.bids.first().filter(|bid| bid.is_active())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a Bid> {
+        self.bids.iter().filter(|bid| bid.is_active())
+    }
+}
+
+pub struct HeapGuard<'a, const D: usize = 2> {
+    heap: &'a mut BiddingHeap<D>,
+    dirty: bool,
+    previous_leaders: Option<BTreeMap<String, i32>>,
+}
+
+impl<'a, const D: usize> Deref for HeapGuard<'a, D> {
+    type Target = [Bid];
+
+    fn deref(&self) -> &[Bid] {
+        &self.heap.bids
+    }
+}
+
+impl<'a, const D: usize> DerefMut for HeapGuard<'a, D> {
+    // any mutable access may have reordered bids, so it marks the heap for rebuilding
+    fn deref_mut(&mut self) -> &mut [Bid] {
+        self.dirty = true;
+        &mut self.heap.bids
+    }
+}
+
+impl<'a, const D: usize> Drop for HeapGuard<'a, D> {
+    fn drop(&mut self) {
+        if self.dirty {
+            self.heap.heapify();
+            let previous_leaders = self.previous_leaders.take();
+            self.heap.notify_leader_change(previous_leaders);
+        }
+    }
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bid_error::BidError;
+    use crate::models::v1::bid_event::BidEvent;
+    use crate::models::v1::bid_id_allocator::BidIdAllocator;
+    use crate::models::v1::bidding_heap::{BiddingHeap, CachedComparator, SpillingHeap};
+    use crate::models::v1::heap_snapshot::HeapSnapshot;
+    use crate::models::v1::removal_reason::RemovalReason;
+    use crate::models::v1::tie_break::TieBreak;
+    use chrono::{DateTime, Duration, Utc};
+    use std::cell::Cell;
+    use std::cmp::Ordering;
+    use std::collections::{HashMap, HashSet};
+
+    struct TestData {}
+
+    impl TestData {
+        fn made_at() -> DateTime<Utc> {
+            DateTime::<Utc>::from_timestamp(1000, 100).unwrap()
+        }
+
+        fn bid(id: i32, amount: i32) -> Bid {
+            Bid {
+                auction_id: String::from("auction_id"),
+                bidder_id: id.to_string(),
+                id,
+                amount,
+                made_at: TestData::made_at() + Duration::seconds(id as i64),
+                removed_at: None,
+                removal_reason: None,
+            }
+        }
+
+        fn heap(amounts: &[i32]) -> BiddingHeap {
+            TestData::heap_with_arity(amounts)
+        }
+
+        fn heap_with_arity<const D: usize>(amounts: &[i32]) -> BiddingHeap<D> {
+            let mut heap = BiddingHeap::<D>::with_arity();
+            for (id, amount) in amounts.iter().enumerate() {
+                heap.push(TestData::bid(id as i32, *amount));
+            }
+            heap
+        }
+    }
+
+    #[test]
+    fn new_works() {
+        let heap = BiddingHeap::new();
+
+        assert!(heap.is_empty());
+        assert_eq!(0, heap.len());
+        assert!(heap.peek().is_none());
+    }
+
+    #[test]
+    fn push_and_peek_work() {
+        let heap = TestData::heap(&[30, 10, 50, 20, 40]);
+
+        assert_eq!(5, heap.len());
+        assert_eq!(50, heap.peek().unwrap().amount);
+    }
+
+    #[test]
+    fn pop_works() {
+        let mut heap = TestData::heap(&[30, 10, 50, 20, 40]);
+
+        assert_eq!(50, heap.pop().unwrap().amount);
+        assert_eq!(40, heap.pop().unwrap().amount);
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn drain_map_works() {
+        let heap = TestData::heap(&[30, 10, 50, 20, 40, 10]);
+
+        let amounts: Vec<i32> = heap.drain_map(|bid| bid.amount);
+        assert_eq!(vec![50, 40, 30, 20, 10, 10], amounts);
+    }
+
+    #[test]
+    fn push_with_bidder_cap_works() {
+        let mut heap = BiddingHeap::new();
+        for id in 0..3 {
+            let mut bid = TestData::bid(id, 100 + id);
+            bid.bidder_id = String::from("bidder_id");
+            assert_eq!(Ok(()), heap.push_with_bidder_cap(bid, 3));
+        }
+        assert_eq!(3, heap.len());
+
+        let mut bid = TestData::bid(3, 103);
+        bid.bidder_id = String::from("bidder_id");
+        assert_eq!(
+            Err(BidError::BidderLimitReached),
+            heap.push_with_bidder_cap(bid, 3)
+        );
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn push_with_bidder_cap_ignores_removed_bids_and_other_auctions() {
+        let mut heap = BiddingHeap::new();
+
+        let mut removed_bid = TestData::bid(0, 100);
+        removed_bid.bidder_id = String::from("bidder_id");
+        removed_bid.remove();
+        heap.push(removed_bid);
+
+        let mut other_auction_bid = TestData::bid(1, 101);
+        other_auction_bid.bidder_id = String::from("bidder_id");
+        other_auction_bid.auction_id = String::from("other_auction_id");
+        heap.push(other_auction_bid);
+
+        let mut bid = TestData::bid(2, 102);
+        bid.bidder_id = String::from("bidder_id");
+        assert_eq!(Ok(()), heap.push_with_bidder_cap(bid, 1));
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn scale_amounts_works() {
+        let mut heap = TestData::heap(&[30, 10, 50, 20, 40]);
+
+        assert_eq!(Ok(()), heap.scale_amounts(100));
+        assert!(heap.is_valid());
+
+        let amounts: Vec<i32> = heap.drain_map(|bid| bid.amount);
+        assert_eq!(vec![5000, 4000, 3000, 2000, 1000], amounts);
+    }
+
+    #[test]
+    fn scale_amounts_overflow_leaves_heap_unchanged() {
+        let mut heap = TestData::heap(&[30, i32::MAX, 50]);
+
+        assert_eq!(Err(BidError::Overflow), heap.scale_amounts(2));
+        assert!(heap.is_valid());
+
+        let amounts: Vec<i32> = heap.drain_map(|bid| bid.amount);
+        assert_eq!(vec![i32::MAX, 50, 30], amounts);
+    }
+
+    #[test]
+    fn leader_works() {
+        let mut heap = TestData::heap(&[30, 10, 50]);
+        let mut other_auction_bid = TestData::bid(3, 100);
+        other_auction_bid.auction_id = String::from("other_auction_id");
+        heap.push(other_auction_bid);
+        let mut removed_bid = TestData::bid(4, 60);
+        removed_bid.remove();
+        heap.push(removed_bid);
+
+        assert_eq!(2, heap.leader("auction_id").unwrap().id);
+        assert_eq!(3, heap.leader("other_auction_id").unwrap().id);
+        assert!(heap.leader("missing_auction_id").is_none());
+    }
+
+    #[test]
+    fn push_if_leader_inserts_winning_bid() {
+        let mut heap = TestData::heap(&[30, 10, 50]);
+
+        assert_eq!(Ok(true), heap.push_if_leader(TestData::bid(3, 60)));
+        assert_eq!(4, heap.len());
+        assert_eq!(3, heap.leader("auction_id").unwrap().id);
+    }
+
+    #[test]
+    fn push_if_leader_rejects_losing_bid() {
+        let mut heap = TestData::heap(&[30, 10, 50]);
+
+        assert_eq!(Ok(false), heap.push_if_leader(TestData::bid(3, 50)));
+        assert_eq!(3, heap.len());
+        assert_eq!(2, heap.leader("auction_id").unwrap().id);
+
+        let mut removed_bid = TestData::bid(4, 60);
+        removed_bid.remove();
+        assert_eq!(Err(BidError::BidInactive), heap.push_if_leader(removed_bid));
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn as_slice_works() {
+        let mut heap = TestData::heap(&[30, 10, 50, 20, 40]);
+        heap.push({
+            let mut removed_bid = TestData::bid(5, 60);
+            removed_bid.remove();
+            removed_bid
+        });
+
+        let slice = heap.as_slice();
+        assert_eq!(heap.len(), slice.len());
+        for id in 0..6 {
+            assert!(slice.iter().any(|bid| bid.id == id));
+        }
+    }
+
+    #[test]
+    fn pop_returns_each_bid_exactly_once() {
+        let mut heap = TestData::heap(&[40, 10, 70, 30, 50, 20, 60]);
+
+        let mut ids: Vec<i32> = Vec::new();
+        while let Some(bid) = heap.pop() {
+            assert!(heap.is_valid());
+            assert!(!heap
+                .as_slice()
+                .iter()
+                .any(|remaining| remaining.id == bid.id));
+            ids.push(bid.id);
+        }
+
+        assert!(heap.is_empty());
+        assert_eq!(vec![2, 6, 4, 0, 3, 5, 1], ids);
+        ids.sort();
+        ids.dedup();
+        assert_eq!(7, ids.len());
+    }
+
+    #[test]
+    fn bottom_n_active_works() {
+        let mut heap = TestData::heap(&[30, 10, 50, 20, 40]);
+        let mut removed_bid = TestData::bid(5, 0);
+        removed_bid.remove();
+        heap.push(removed_bid);
+
+        let ids: Vec<i32> = heap.bottom_n_active(3).iter().map(|bid| bid.id).collect();
+        assert_eq!(vec![1, 3, 0], ids);
+    }
+
+    #[test]
+    fn bottom_n_active_handles_n_of_zero() {
+        let heap = TestData::heap(&[30, 10, 50]);
+
+        assert!(heap.bottom_n_active(0).is_empty());
+    }
+
+    #[test]
+    fn bottom_n_active_handles_n_beyond_active_count() {
+        let mut heap = TestData::heap(&[30, 10, 50]);
+        let mut removed_bid = TestData::bid(3, 0);
+        removed_bid.remove();
+        heap.push(removed_bid);
+
+        let ids: Vec<i32> = heap.bottom_n_active(10).iter().map(|bid| bid.id).collect();
+        assert_eq!(vec![1, 0, 2], ids);
+    }
+
+    #[test]
+    fn total_active_amount_works() {
+        let mut heap = TestData::heap(&[i32::MAX, i32::MAX, 10]);
+        let mut removed_bid = TestData::bid(3, 1000);
+        removed_bid.remove();
+        heap.push(removed_bid);
+        let mut other_auction_bid = TestData::bid(4, 1000);
+        other_auction_bid.auction_id = String::from("other_auction_id");
+        heap.push(other_auction_bid);
+
+        assert_eq!(
+            2 * i64::from(i32::MAX) + 10,
+            heap.total_active_amount("auction_id")
+        );
+        assert_eq!(1000, heap.total_active_amount("other_auction_id"));
+        assert_eq!(0, heap.total_active_amount("missing_auction_id"));
+    }
+
+    #[test]
+    fn push_idempotent_works() {
+        let mut heap = TestData::heap(&[30, 10]);
+
+        assert!(heap.push_idempotent(TestData::bid(2, 50)));
+        assert!(!heap.push_idempotent(TestData::bid(2, 50)));
+
+        let mut retried_bid = TestData::bid(2, 50);
+        retried_bid.id = 3;
+        assert!(!heap.push_idempotent(retried_bid));
+
+        assert_eq!(3, heap.len());
+        assert_eq!(
+            1,
+            heap.as_slice()
+                .iter()
+                .filter(|bid| bid.amount == 50)
+                .count()
+        );
+    }
+
+    #[test]
+    fn push_idempotent_accepts_distinct_bids() {
+        let mut heap = TestData::heap(&[30]);
+
+        let mut higher_bid = TestData::bid(0, 40);
+        higher_bid.id = 1;
+        assert!(heap.push_idempotent(higher_bid));
+        assert_eq!(2, heap.len());
+    }
+
+    #[test]
+    fn push_with_rate_limit_rejects_bids_within_interval() {
+        let mut heap = BiddingHeap::new();
+        let mut first_bid = TestData::bid(0, 100);
+        first_bid.bidder_id = String::from("bidder_id");
+        assert_eq!(
+            Ok(()),
+            heap.push_with_rate_limit(first_bid, Duration::seconds(10))
+        );
+
+        let mut second_bid = TestData::bid(9, 200);
+        second_bid.bidder_id = String::from("bidder_id");
+        assert_eq!(
+            Err(BidError::RateLimited),
+            heap.push_with_rate_limit(second_bid, Duration::seconds(10))
+        );
+        assert_eq!(1, heap.len());
+    }
+
+    #[test]
+    fn push_with_rate_limit_accepts_bids_outside_interval() {
+        let mut heap = BiddingHeap::new();
+        let mut first_bid = TestData::bid(0, 100);
+        first_bid.bidder_id = String::from("bidder_id");
+        assert_eq!(
+            Ok(()),
+            heap.push_with_rate_limit(first_bid, Duration::seconds(10))
+        );
+
+        let mut second_bid = TestData::bid(10, 200);
+        second_bid.bidder_id = String::from("bidder_id");
+        assert_eq!(
+            Ok(()),
+            heap.push_with_rate_limit(second_bid, Duration::seconds(10))
+        );
+
+        let other_bidder_bid = TestData::bid(11, 300);
+        assert_eq!(
+            Ok(()),
+            heap.push_with_rate_limit(other_bidder_bid, Duration::seconds(10))
+        );
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn leader_age_works() {
+        let heap = TestData::heap(&[30, 10, 50]);
+        let now = TestData::made_at() + Duration::minutes(5);
+
+        assert_eq!(
+            Some(Duration::minutes(5) - Duration::seconds(2)),
+            heap.leader_age("auction_id", now)
+        );
+        assert_eq!(None, heap.leader_age("missing_auction_id", now));
+    }
+
+    #[test]
+    fn active_view_works() {
+        let mut heap = TestData::heap(&[30, 10]);
+        for (id, amount) in [(2, 50), (3, 40)] {
+            let mut removed_bid = TestData::bid(id, amount);
+            removed_bid.remove();
+            heap.push(removed_bid);
+        }
+
+        let view = heap.active_view();
+        assert_eq!(2, view.len());
+        assert!(!view.is_empty());
+        assert_eq!(0, view.peek().unwrap().id);
+        assert!(view.iter().all(|bid| bid.is_active()));
+        assert_eq!(2, view.iter().count());
+    }
+
+    #[test]
+    fn active_view_hides_only_removed_bids() {
+        let mut heap = BiddingHeap::new();
+        let mut removed_bid = TestData::bid(0, 50);
+        removed_bid.remove();
+        heap.push(removed_bid);
+
+        let view = heap.active_view();
+        assert_eq!(1, heap.len());
+        assert_eq!(0, view.len());
+        assert!(view.is_empty());
+        assert!(view.peek().is_none());
+        assert_eq!(0, view.iter().count());
+    }
+
+    #[test]
+    fn remove_works() {
+        let mut heap = TestData::heap(&[30, 10, 50, 20, 40]);
+
+        assert!(heap.remove(2));
+        assert!(heap.is_valid());
+        assert_eq!(5, heap.len());
+        assert_eq!(4, heap.leader("auction_id").unwrap().id);
+        assert!(!heap
+            .as_slice()
+            .iter()
+            .find(|bid| bid.id == 2)
+            .unwrap()
+            .is_active());
+
+        assert!(!heap.remove(2));
+        assert!(!heap.remove(100));
+    }
+
+    #[test]
+    fn remove_records_tombstone() {
+        let mut heap = TestData::heap(&[30, 10, 50]);
+        assert!(heap.tombstones().is_empty());
+
+        heap.remove(2);
+        heap.scale_amounts(2).unwrap();
+
+        assert_eq!(1, heap.tombstones().len());
+        let tombstone = &heap.tombstones()[0];
+        assert_eq!(2, tombstone.id);
+        assert_eq!(50, tombstone.amount);
+        assert!(tombstone.is_active());
+        assert_eq!(None, tombstone.removal_reason);
+    }
+
+    #[test]
+    fn compact_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 30, 50, 20, 60]);
+        for id in [0, 2, 5] {
+            heap.remove(id);
+        }
+
+        assert_eq!(3, heap.compact());
+        assert_eq!(4, heap.len());
+        assert_eq!(heap.active_view().len(), heap.len());
+        assert!(heap.is_valid());
+        assert_eq!(3, heap.tombstones().len());
+
+        let ids: Vec<i32> = heap.drain_map(|bid| bid.id);
+        assert_eq!(vec![6, 4, 3, 1], ids);
+    }
+
+    #[test]
+    fn remove_ids_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 30, 50, 20, 60]);
+
+        assert_eq!(3, heap.remove_ids(&[2, 4, 5, 100]));
+        assert!(heap.is_valid());
+        assert_eq!(7, heap.len());
+        assert_eq!(4, heap.active_view().len());
+        assert_eq!(3, heap.tombstones().len());
+        assert_eq!(0, heap.remove_ids(&[2]));
+
+        let ids: Vec<i32> = heap.drain_map(|bid| bid.id);
+        assert_eq!(vec![6, 0, 3, 1], ids[..4].to_vec());
+    }
+
+    #[test]
+    fn drain_sorted_by_works() {
+        let heap = TestData::heap(&[40, 10, 70, 30, 50, 20, 60]);
+
+        let ids: Vec<i32> = heap
+            .drain_sorted_by(|a, b| b.cmp_bids(a))
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+        assert_eq!(vec![1, 5, 3, 0, 4, 6, 2], ids);
+    }
+
+    #[test]
+    fn cached_comparator_reduces_comparisons() {
+        let amounts = [40, 10, 70, 30, 50, 20, 60, 80, 90, 15, 25, 35, 45, 55, 65];
+        let uncached_calls = Cell::new(0);
+        let uncached_ids: Vec<i32> = TestData::heap(&amounts)
+            .drain_sorted_by(|a, b| {
+                uncached_calls.set(uncached_calls.get() + 1);
+                a.cmp_bids(b)
+            })
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+
+        let cached_calls = Cell::new(0);
+        let mut comparator = CachedComparator::new(|a: &Bid, b: &Bid| {
+            cached_calls.set(cached_calls.get() + 1);
+            a.cmp_bids(b)
+        });
+        let cached_ids: Vec<i32> = TestData::heap(&amounts)
+            .drain_sorted_by(|a, b| comparator.compare(a, b))
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+
+        assert_eq!(uncached_ids, cached_ids);
+        assert!(cached_calls.get() < uncached_calls.get());
+    }
+
+    #[test]
+    fn leaderboard_since_works() {
+        let mut heap = TestData::heap(&[90, 10, 70, 30, 50, 20, 60]);
+        let mut other_auction_bid = TestData::bid(7, 100);
+        other_auction_bid.auction_id = String::from("other_auction_id");
+        heap.push(other_auction_bid);
+        heap.remove(4);
+
+        let since = TestData::made_at() + Duration::seconds(1);
+        let ids: Vec<i32> = heap
+            .leaderboard_since("auction_id", since, 3)
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+        assert_eq!(vec![2, 6, 3], ids);
+
+        let ids: Vec<i32> = heap
+            .leaderboard_since("auction_id", since, 10)
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+        assert_eq!(vec![2, 6, 3, 5], ids);
+    }
+
+    #[test]
+    fn rapid_self_bids_works() {
+        let mut heap = BiddingHeap::new();
+        for (id, bidder_id, amount) in [
+            (0, "honest", 10),
+            (1, "shill", 20),
+            (2, "shill", 30),
+            (3, "honest", 40),
+            (60, "honest", 50),
+            (61, "other", 60),
+        ] {
+            let mut bid = TestData::bid(id, amount);
+            bid.bidder_id = String::from(bidder_id);
+            heap.push(bid);
+        }
+
+        assert_eq!(
+            vec![String::from("shill")],
+            heap.rapid_self_bids("auction_id", Duration::seconds(5))
+        );
+        assert_eq!(
+            vec![String::from("shill"), String::from("honest")],
+            heap.rapid_self_bids("auction_id", Duration::minutes(1))
+        );
+        assert!(heap
+            .rapid_self_bids("missing_auction_id", Duration::minutes(1))
+            .is_empty());
+    }
+
+    #[test]
+    fn drain_sorted_works() {
+        let heap = TestData::heap(&[30, 10, 50, 20, 40]);
+
+        let ids: Vec<i32> = heap.drain_sorted().iter().map(|bid| bid.id).collect();
+        assert_eq!(vec![2, 4, 0, 3, 1], ids);
+    }
+
+    #[test]
+    fn empty_heap_does_not_panic() {
+        let mut heap = BiddingHeap::new();
+        assert!(heap.peek().is_none());
+        assert!(heap.pop().is_none());
+        assert!(heap.pop().is_none());
+        assert!(!heap.remove(0));
+        assert!(heap.leader("auction_id").is_none());
+
+        assert!(BiddingHeap::new().drain_sorted().is_empty());
+        assert!(BiddingHeap::new().drain_map(|bid| bid.id).is_empty());
+        assert!(BiddingHeap::new()
+            .drain_sorted_by(|a, b| a.cmp_bids(b))
+            .is_empty());
+    }
+
+    #[test]
+    fn reserve_works() {
+        let mut heap = TestData::heap(&[30, 10]);
+
+        heap.reserve(100);
+        assert!(heap.capacity() >= heap.len() + 100);
+
+        let capacity = heap.capacity();
+        let storage = heap.as_slice().as_ptr();
+        for id in 2..102 {
+            heap.push(TestData::bid(id, id));
+        }
+        assert_eq!(capacity, heap.capacity());
+        assert_eq!(storage, heap.as_slice().as_ptr());
+    }
+
+    #[test]
+    fn into_iter_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 30, 50, 20, 60]);
+        heap.remove(2);
+
+        let mut bids: Vec<Bid> = Vec::new();
+        for bid in heap {
+            bids.push(bid);
+        }
+
+        let ids: Vec<i32> = bids.iter().map(|bid| bid.id).collect();
+        assert_eq!(vec![6, 4, 0, 3, 5, 1, 2], ids);
+        for (index, bid) in bids.iter().enumerate() {
+            for lower_bid in &bids[index + 1..] {
+                assert!(bid.is_higher_bid_than(lower_bid));
+            }
+        }
+    }
+
+    #[test]
+    fn into_iter_by_reference_works() {
+        let heap = TestData::heap(&[40, 10, 70, 30, 50, 20, 60]);
+
+        let mut count = 0;
+        for (bid, heap_bid) in (&heap).into_iter().zip(heap.as_slice()) {
+            assert_eq!(heap_bid, bid);
+            count += 1;
+        }
+        assert_eq!(heap.len(), count);
+        assert_eq!(heap.len(), heap.into_iter().size_hint().0);
+    }
+
+    #[test]
+    fn to_dot_works() {
+        let heap = TestData::heap(&[40, 10, 70, 30, 50, 20, 60]);
+
+        let dot = heap.to_dot();
+        assert!(dot.starts_with("digraph bidding_heap {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(7, dot.matches("[label=").count());
+        assert_eq!(6, dot.matches(" -> ").count());
+        assert!(dot.contains("    0 [label=\"id: 2, amount: 70\"];\n"));
+        assert!(dot.contains("    2 -> 6;\n"));
+    }
+
+    #[test]
+    fn removed_by_reason_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 30, 50]);
+        heap.remove_with_reason(0, RemovalReason::Withdrawn);
+        heap.remove_with_reason(2, RemovalReason::Outbid);
+        heap.remove_with_reason(3, RemovalReason::Outbid);
+        heap.remove(4);
+        assert!(heap.is_valid());
+
+        let ids = |reason| -> Vec<i32> {
+            let mut ids: Vec<i32> = heap
+                .removed_by_reason(reason)
+                .iter()
+                .map(|bid| bid.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(vec![0], ids(RemovalReason::Withdrawn));
+        assert_eq!(vec![2, 3], ids(RemovalReason::Outbid));
+        assert!(ids(RemovalReason::Expired).is_empty());
+        assert!(ids(RemovalReason::Disqualified).is_empty());
+    }
+
+    #[test]
+    fn winning_bids_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 30, 50]);
+        heap.remove(2);
+
+        let ids: Vec<i32> = heap
+            .winning_bids("auction_id", 2)
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+        assert_eq!(vec![4, 0], ids);
+    }
+
+    #[test]
+    fn winning_bids_handles_fewer_bids_than_units() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        heap.remove(2);
+
+        let ids: Vec<i32> = heap
+            .winning_bids("auction_id", 5)
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+        assert_eq!(vec![0, 1], ids);
+        assert!(heap.winning_bids("missing_auction_id", 5).is_empty());
+    }
+
+    #[test]
+    fn clearing_price_works() {
+        let heap = TestData::heap(&[40, 10, 70, 30, 50]);
+
+        assert_eq!(Some(50), heap.clearing_price("auction_id", 2));
+        assert_eq!(Some(10), heap.clearing_price("auction_id", 5));
+        assert_eq!(Some(10), heap.clearing_price("auction_id", 8));
+        assert_eq!(None, heap.clearing_price("auction_id", 0));
+        assert_eq!(None, heap.clearing_price("missing_auction_id", 2));
+    }
+
+    #[test]
+    fn leader_snapshot_works() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+
+        let snapshot = heap.leader_snapshot("auction_id").unwrap();
+        assert_eq!(heap.leader("auction_id"), Some(&snapshot));
+
+        heap.remove(2);
+        heap.scale_amounts(2).unwrap();
+        assert_eq!(2, snapshot.id);
+        assert_eq!(70, snapshot.amount);
+        assert!(snapshot.is_active());
+        assert_eq!(0, heap.leader("auction_id").unwrap().id);
+        assert!(heap.leader_snapshot("missing_auction_id").is_none());
+    }
+
+    #[test]
+    fn collapse_bidder_bids_works() {
+        let mut heap = BiddingHeap::new();
+        for (id, bidder_id, amount) in [
+            (0, "bidder_id", 30),
+            (1, "bidder_id", 50),
+            (2, "bidder_id", 40),
+            (3, "other_bidder_id", 20),
+        ] {
+            let mut bid = TestData::bid(id, amount);
+            bid.bidder_id = String::from(bidder_id);
+            heap.push(bid);
+        }
+        let mut other_auction_bid = TestData::bid(4, 10);
+        other_auction_bid.bidder_id = String::from("bidder_id");
+        other_auction_bid.auction_id = String::from("other_auction_id");
+        heap.push(other_auction_bid);
+
+        assert_eq!(2, heap.collapse_bidder_bids("auction_id"));
+        assert!(heap.is_valid());
+
+        let mut active_ids: Vec<i32> = heap.active_view().iter().map(|bid| bid.id).collect();
+        active_ids.sort();
+        assert_eq!(vec![1, 3, 4], active_ids);
+        assert_eq!(0, heap.collapse_bidder_bids("auction_id"));
+    }
+
+    #[test]
+    fn arity_does_not_change_drain_order() {
+        let amounts = [
+            40, 10, 70, 30, 50, 20, 60, 80, 90, 15, 25, 35, 45, 55, 65, 50, 50,
+        ];
+        let mut binary_heap = TestData::heap_with_arity::<2>(&amounts);
+        let mut quaternary_heap = TestData::heap_with_arity::<4>(&amounts);
+        assert!(binary_heap.is_valid());
+        assert!(quaternary_heap.is_valid());
+
+        binary_heap.remove_ids(&[3, 8]);
+        quaternary_heap.remove_ids(&[3, 8]);
+        assert!(binary_heap.is_valid());
+        assert!(quaternary_heap.is_valid());
+
+        let mut binary_ids: Vec<i32> = Vec::new();
+        let mut quaternary_ids: Vec<i32> = Vec::new();
+        while let (Some(binary_bid), Some(quaternary_bid)) =
+            (binary_heap.pop(), quaternary_heap.pop())
+        {
+            assert!(binary_heap.is_valid());
+            assert!(quaternary_heap.is_valid());
+            binary_ids.push(binary_bid.id);
+            quaternary_ids.push(quaternary_bid.id);
+        }
+
+        assert_eq!(amounts.len(), quaternary_ids.len());
+        assert_eq!(binary_ids, quaternary_ids);
+    }
+
+    #[test]
+    fn arity_is_reflected_in_layout() {
+        let heap = TestData::heap_with_arity::<4>(&[10, 20, 30, 40, 50, 60]);
+
+        let dot = heap.to_dot();
+        assert!(dot.contains("    0 -> 4;\n"));
+        assert!(dot.contains("    1 -> 5;\n"));
+    }
+
+    #[test]
+    fn partition_by_amount_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 30, 50, 20]);
+        heap.remove(5);
+
+        let (below, at_or_above) = heap.partition_by_amount(40);
+        let mut below_ids: Vec<i32> = below.iter().map(|bid| bid.id).collect();
+        let mut at_or_above_ids: Vec<i32> = at_or_above.iter().map(|bid| bid.id).collect();
+        below_ids.sort();
+        at_or_above_ids.sort();
+
+        assert_eq!(vec![1, 3], below_ids);
+        assert_eq!(vec![0, 2, 4], at_or_above_ids);
+    }
+
+    #[test]
+    fn from_vec_works() {
+        let bids: Vec<Bid> = [40, 10, 70, 30, 50, 20, 60]
+            .iter()
+            .enumerate()
+            .map(|(id, amount)| TestData::bid(id as i32, *amount))
+            .collect();
+
+        let heap: BiddingHeap = BiddingHeap::from(bids.clone());
+        assert!(heap.is_valid());
+        assert_eq!(bids.len(), heap.len());
+
+        let round_tripped: Vec<Bid> = heap.into();
+        let ids: Vec<i32> = round_tripped.iter().map(|bid| bid.id).collect();
+        assert_eq!(vec![2, 6, 4, 0, 3, 5, 1], ids);
+        for bid in &bids {
+            assert_eq!(1, round_tripped.iter().filter(|b| *b == bid).count());
+        }
+    }
+
+    #[test]
+    fn from_vec_works_with_arity() {
+        let bids: Vec<Bid> = [40, 10, 70, 30, 50, 20, 60]
+            .iter()
+            .enumerate()
+            .map(|(id, amount)| TestData::bid(id as i32, *amount))
+            .collect();
+
+        let heap = BiddingHeap::<4>::from(bids);
+        assert!(heap.is_valid());
+
+        let ids: Vec<i32> = Vec::from(heap).iter().map(|bid| bid.id).collect();
+        assert_eq!(vec![2, 6, 4, 0, 3, 5, 1], ids);
+    }
+
+    #[test]
+    fn winner_with_reserve_works() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        heap.remove(2);
+
+        assert_eq!(None, heap.winner_with_reserve("auction_id", 41));
+        assert_eq!(0, heap.winner_with_reserve("auction_id", 40).unwrap().id);
+        assert_eq!(None, heap.winner_with_reserve("missing_auction_id", 0));
+    }
+
+    #[test]
+    fn on_leader_change_works() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut heap = TestData::heap(&[40, 10]);
+        let leaders = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&leaders);
+        heap.on_leader_change(move |leader| recorded.borrow_mut().push(leader.id));
+
+        heap.push(TestData::bid(2, 70));
+        assert_eq!(vec![2], *leaders.borrow());
+
+        heap.push(TestData::bid(3, 20));
+        assert_eq!(vec![2], *leaders.borrow());
+
+        heap.remove(1);
+        assert_eq!(vec![2], *leaders.borrow());
+
+        heap.remove(2);
+        assert_eq!(vec![2, 0], *leaders.borrow());
+    }
+
+    #[test]
+    fn on_leader_change_works_per_auction() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let other_bid = |id, amount| {
+            let mut bid = TestData::bid(id, amount);
+            bid.auction_id = String::from("other_auction_id");
+            bid
+        };
+        let mut heap = TestData::heap(&[90, 10]);
+        heap.push(other_bid(2, 40));
+        let leaders = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&leaders);
+        heap.on_leader_change(move |leader| recorded.borrow_mut().push(leader.id));
+
+        // a new leader below the heap-wide root still fires
+        heap.push(other_bid(3, 50));
+        assert_eq!(vec![3], *leaders.borrow());
+
+        heap.remove_ids(&[0]);
+        assert_eq!(vec![3, 1], *leaders.borrow());
+
+        heap.scale_amounts(2).unwrap();
+        heap.collapse_bidder_bids("auction_id");
+        assert_eq!(vec![3, 1], *leaders.borrow());
+
+        assert_eq!(Some(3), heap.pop().map(|bid| bid.id));
+        assert_eq!(vec![3, 1, 2], *leaders.borrow());
+
+        heap.remove_where(|bid| bid.id == 1);
+        assert_eq!(vec![3, 1, 2], *leaders.borrow());
+
+        {
+            let mut bids = heap.as_mut_slice();
+            for bid in bids.iter_mut().filter(|bid| bid.id == 2) {
+                bid.amount = 1;
+            }
+        }
+        assert_eq!(vec![3, 1, 2], *leaders.borrow());
+
+        let mut checkpoint = heap.snapshot();
+        checkpoint.bids.push(other_bid(4, 60));
+        let diff = BiddingHeap::<2>::from(checkpoint).diff_since(&heap.snapshot());
+        heap.apply_diff(&diff);
+        assert_eq!(vec![3, 1, 2, 4], *leaders.borrow());
+    }
+
+    #[test]
+    fn increment_gaps_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 40]);
+        heap.remove(3);
+
+        assert_eq!(vec![30, 0, 30], heap.increment_gaps("auction_id"));
+        assert!(heap.increment_gaps("missing_auction_id").is_empty());
+    }
+
+    #[test]
+    fn bids_per_bidder_works() {
+        let mut heap = BiddingHeap::new();
+        for (id, bidder_id, auction_id) in [
+            (0, "alice", "auction_id"),
+            (1, "bob", "auction_id"),
+            (2, "alice", "other_auction_id"),
+            (3, "alice", "auction_id"),
+            (4, "carol", "auction_id"),
+        ] {
+            let mut bid = TestData::bid(id, 10 * id);
+            bid.bidder_id = String::from(bidder_id);
+            bid.auction_id = String::from(auction_id);
+            heap.push(bid);
+        }
+        heap.remove(4);
+
+        let counts = heap.bids_per_bidder();
+        assert_eq!(2, counts.len());
+        assert_eq!(Some(&3), counts.get("alice"));
+        assert_eq!(Some(&1), counts.get("bob"));
+        assert_eq!(None, counts.get("carol"));
+    }
+
+    #[test]
+    fn activity_timeline_works() {
+        let mut heap = TestData::heap(&[10, 20, 30, 40, 50]);
+        let mut other_bid = TestData::bid(5, 60);
+        other_bid.auction_id = String::from("other_auction_id");
+        heap.push(other_bid);
+        heap.remove(3);
+
+        let timeline = heap
+            .activity_timeline("auction_id", Duration::seconds(2))
+            .unwrap();
+        let bucket_start = |seconds| DateTime::from_timestamp(seconds, 0).unwrap();
+        assert_eq!(
+            vec![
+                (bucket_start(1000), 2),
+                (bucket_start(1002), 2),
+                (bucket_start(1004), 1)
+            ],
+            timeline.into_iter().collect::<Vec<_>>()
+        );
+        assert!(heap
+            .activity_timeline("missing_auction_id", Duration::seconds(2))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Err(BidError::InvalidBucket),
+            heap.activity_timeline("auction_id", Duration::zero())
+        );
+        assert_eq!(
+            Err(BidError::InvalidBucket),
+            heap.activity_timeline("auction_id", Duration::seconds(-2))
+        );
+    }
+
+    #[test]
+    fn activity_timeline_works_past_2262() {
+        let mut heap = BiddingHeap::new();
+        let mut bid = TestData::bid(0, 10);
+        bid.made_at = DateTime::from_timestamp(10_000_000_005, 0).unwrap();
+        heap.push(bid);
+
+        let timeline = heap
+            .activity_timeline("auction_id", Duration::seconds(10))
+            .unwrap();
+        assert_eq!(
+            vec![(DateTime::from_timestamp(10_000_000_000, 0).unwrap(), 1)],
+            timeline.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn preview_rank_works() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        heap.remove(2);
+
+        assert_eq!(0, heap.preview_rank(&TestData::bid(3, 50)));
+        assert_eq!(2, heap.preview_rank(&TestData::bid(3, 5)));
+        // the equal existing bid was made earlier, so it stays ahead
+        assert_eq!(2, heap.preview_rank(&TestData::bid(3, 10)));
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn preview_rank_ignores_other_auctions() {
+        let heap = TestData::heap(&[40, 10]);
+        let mut bid = TestData::bid(2, 5);
+        bid.auction_id = String::from("other_auction_id");
+
+        assert_eq!(0, heap.preview_rank(&bid));
+    }
+
+    #[test]
+    fn median_bid_works_for_odd_counts() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 55, 90]);
+        heap.remove(5);
+
+        assert_eq!(0, heap.median_bid().unwrap().id);
+    }
+
+    #[test]
+    fn median_bid_works_for_even_counts() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 55]);
+        heap.remove(2);
+
+        assert_eq!(3, heap.median_bid().unwrap().id);
+        assert_eq!(None, BiddingHeap::new().median_bid());
+    }
+
+    #[test]
+    fn remove_where_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 55]);
+        heap.remove(3);
+
+        let removed = heap.remove_where(|bid| bid.amount < 50);
+        assert_eq!(
+            vec![0, 1],
+            removed.iter().map(|bid| bid.id).collect::<Vec<_>>()
+        );
+        assert!(removed.iter().all(|bid| !bid.is_active()));
+        assert_eq!(3, heap.tombstones().len());
+        assert!(heap.is_valid());
+        assert_eq!(
+            vec![2, 4],
+            heap.active_view()
+                .iter()
+                .map(|bid| bid.id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(5, heap.len());
+    }
+
+    #[test]
+    fn reassign_ids_rejects_overflowing_range() {
+        let mut heap = TestData::heap(&[40, 10]);
+
+        assert_eq!(Err(BidError::IdOverflow), heap.reassign_ids(i32::MAX - 1));
+        let mut ids: Vec<i32> = heap.as_slice().iter().map(|bid| bid.id).collect();
+        ids.sort_unstable();
+        assert_eq!(vec![0, 1], ids);
+        assert_eq!(Ok(i32::MAX), heap.reassign_ids(i32::MAX - 2));
+    }
+
+    #[test]
+    fn reassign_ids_works() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        heap.push(TestData::bid(0, 25));
+        heap.push(TestData::bid(1, 55));
+        heap.remove(2);
+        let amounts_before: Vec<i32> = heap.as_slice().iter().map(|bid| bid.amount).collect();
+
+        assert_eq!(Ok(105), heap.reassign_ids(100));
+
+        let mut ids: Vec<i32> = heap.as_slice().iter().map(|bid| bid.id).collect();
+        ids.sort_unstable();
+        assert_eq!((100..105).collect::<Vec<_>>(), ids);
+        assert_eq!(
+            amounts_before,
+            heap.as_slice()
+                .iter()
+                .map(|bid| bid.amount)
+                .collect::<Vec<_>>()
+        );
+        assert!(heap.is_valid());
+    }
+
+    #[test]
+    fn from_json_works() {
+        let bids = vec![
+            TestData::bid(0, 40),
+            TestData::bid(1, 70),
+            TestData::bid(2, 10),
+        ];
+        let data = serde_json::to_string(&bids).unwrap();
+
+        let heap = BiddingHeap::from_json(&data).unwrap();
+        assert!(heap.is_valid());
+        assert_eq!(1, heap.peek().unwrap().id);
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn from_json_reports_invalid_bid_index() {
+        let mut future_bid = TestData::bid(1, 70);
+        future_bid.made_at = Utc::now() + Duration::days(1);
+        let bids = vec![TestData::bid(0, 40), future_bid, TestData::bid(2, 10)];
+        let data = serde_json::to_string(&bids).unwrap();
+
+        assert_eq!(
+            Err(BidError::InvalidBidAt(1, Box::new(BidError::FutureDated))),
+            BiddingHeap::from_json(&data).map(|heap| heap.len())
+        );
+        assert!(matches!(
+            BiddingHeap::from_json("[{}]"),
+            Err(BidError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn from_ndjson_works() {
+        let mut data = String::new();
+        for bid in [
+            TestData::bid(0, 40),
+            TestData::bid(1, 70),
+            TestData::bid(2, 10),
+        ] {
+            data.push_str(&serde_json::to_string(&bid).unwrap());
+            data.push('\n');
+        }
+        data.push('\n');
+
+        let heap = BiddingHeap::from_ndjson(data.as_bytes()).unwrap();
+        assert!(heap.is_valid());
+        assert_eq!(1, heap.peek().unwrap().id);
+        assert_eq!(3, heap.len());
+    }
+
+    #[test]
+    fn from_ndjson_reports_malformed_line() {
+        let data = format!(
+            "{}\n{{\"id\": 1\n{}\n",
+            serde_json::to_string(&TestData::bid(0, 40)).unwrap(),
+            serde_json::to_string(&TestData::bid(2, 10)).unwrap()
+        );
+
+        assert!(matches!(
+            BiddingHeap::from_ndjson(data.as_bytes()),
+            Err(BidError::InvalidLine(2, error)) if matches!(*error, BidError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn write_ndjson_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25]);
+        heap.remove(2);
+        let mut data = Vec::new();
+        heap.write_ndjson(&mut data).unwrap();
+
+        let written_ids: Vec<i32> = String::from_utf8(data.clone())
+            .unwrap()
+            .lines()
+            .map(|line| Bid::from_json(line).unwrap().id)
+            .collect();
+        assert_eq!(vec![0, 3, 1, 2], written_ids);
+
+        let read_back = BiddingHeap::from_ndjson(data.as_slice()).unwrap();
+        let drained_ids: Vec<i32> = read_back.drain_sorted().iter().map(|bid| bid.id).collect();
+        assert_eq!(written_ids, drained_ids);
+    }
+
+    #[test]
+    fn heal_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 55]);
+        assert!(!heap.heal());
+
+        heap.bids[0].amount = 0;
+        heap.bids[4].amount = 100;
+        assert!(!heap.is_valid());
+
+        assert!(heap.heal());
+        assert!(heap.is_valid());
+        assert_eq!(100, heap.peek().unwrap().amount);
+        assert!(!heap.heal());
+    }
+
+    #[test]
+    fn validate_index_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 55]);
+        assert!(heap.validate_index());
+
+        heap.bids[4].amount = 100;
+        assert!(!heap.validate_index());
+        heap.heal();
+        assert!(heap.validate_index());
+
+        // the same id in another auction is a different bid
+        let mut other_auction = TestData::bid(0, 5);
+        other_auction.auction_id = String::from("other_auction_id");
+        heap.push(other_auction);
+        assert!(heap.validate_index());
+
+        heap.push(TestData::bid(0, 5));
+        assert!(heap.is_valid());
+        assert!(!heap.validate_index());
+    }
+
+    #[test]
+    fn as_mut_slice_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 55]);
+        {
+            let mut bids = heap.as_mut_slice();
+            for bid in bids.iter_mut() {
+                if bid.amount < 30 {
+                    bid.amount += 100;
+                }
+            }
+            assert_eq!(5, bids.len());
+        }
+
+        assert!(heap.is_valid());
+        assert_eq!(3, heap.peek().unwrap().id);
+        assert_eq!(
+            vec![3, 1, 2, 4, 0],
+            heap.drain_sorted()
+                .iter()
+                .map(|bid| bid.id)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fair_leader_works() {
+        let mut heap = TestData::heap(&[70, 10, 70, 70, 40]);
+        heap.remove(3);
+
+        let leader_id = |seed| heap.fair_leader("auction_id", seed).unwrap().id;
+        assert_eq!(leader_id(7), leader_id(7));
+        assert!((0..64).all(|seed| [0, 2].contains(&leader_id(seed))));
+        assert!((0..64).any(|seed| leader_id(seed) != leader_id(0)));
+        // fnv1a_64 is fixed, so these picks hold on any toolchain
+        assert_eq!(
+            vec![0, 2, 2, 0],
+            (0..4).map(leader_id).collect::<Vec<i32>>()
+        );
+        assert_eq!(None, heap.fair_leader("missing_auction_id", 7));
+    }
+
+    #[test]
+    fn full_ranking_works() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        let mut tied_bid = TestData::bid(3, 40);
+        tied_bid.made_at = TestData::made_at();
+        heap.push(tied_bid);
+
+        let ranking: Vec<(usize, i32)> = heap
+            .full_ranking("auction_id")
+            .into_iter()
+            .map(|(rank, bid)| (rank, bid.amount))
+            .collect();
+        assert_eq!(vec![(1, 70), (2, 40), (2, 40), (4, 10)], ranking);
+        assert!(heap.full_ranking("missing_auction_id").is_empty());
+    }
+
+    #[test]
+    fn trim_lowest_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25]);
+        heap.remove(1);
+
+        let trimmed = heap.trim_lowest().unwrap();
+        assert_eq!(3, trimmed.id);
+        assert!(!trimmed.is_active());
+        assert!(heap.is_valid());
+        assert_eq!(
+            vec![2, 0],
+            heap.active_view()
+                .iter()
+                .map(|bid| bid.id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![1, 3],
+            heap.tombstones()
+                .iter()
+                .map(|bid| bid.id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(None, BiddingHeap::new().trim_lowest());
+    }
+
+    #[test]
+    fn drain_order_is_pinned_for_fixed_fixture() {
+        // a regression guard: any change to comparison logic that reorders this fixture fails here
+        // - (id, amount, seconds, nanoseconds, removed), with timestamps fixed rather than now()
+        let fixture = [
+            (0, 500, 10, 0, false),
+            (1, 500, 10, 1, false),
+            (2, 700, 20, 0, false),
+            (3, 700, 5, 0, true),
+            (4, 300, 1, 0, false),
+            (5, 500, 3, 0, false),
+            (6, 900, 30, 0, true),
+            (7, 300, 1, 0, true),
+            (8, 100, 40, 0, false),
+            (9, 700, 25, 0, false),
+        ];
+        let bids: Vec<Bid> = fixture
+            .iter()
+            .map(|&(id, amount, seconds, nanoseconds, removed)| Bid {
+                auction_id: String::from("auction_id"),
+                bidder_id: id.to_string(),
+                id,
+                amount,
+                made_at: DateTime::from_timestamp(1_600_000_000 + seconds, nanoseconds).unwrap(),
+                removed_at: if removed {
+                    DateTime::from_timestamp(1_600_000_100, 0)
+                } else {
+                    None
+                },
+                removal_reason: None,
+            })
+            .collect();
+        let expected_ids = vec![2, 9, 5, 0, 1, 4, 8, 6, 3, 7];
+
+        let drained_ids = |heap: Vec<Bid>| heap.iter().map(|bid| bid.id).collect::<Vec<_>>();
+        let mut pushed = BiddingHeap::new();
+        for bid in bids.iter().rev().cloned() {
+            pushed.push(bid);
+        }
+        assert_eq!(expected_ids, drained_ids(pushed.drain_sorted()));
+        assert_eq!(
+            expected_ids,
+            drained_ids(BiddingHeap::<2>::from(bids.clone()).drain_sorted())
+        );
+        assert_eq!(
+            expected_ids,
+            drained_ids(BiddingHeap::<4>::from(bids).drain_sorted())
+        );
+    }
+
+    #[test]
+    fn amount_to_lead_works_for_trailing_bidder() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        let mut second_bid = TestData::bid(3, 25);
+        second_bid.bidder_id = String::from("1");
+        heap.push(second_bid);
+
+        assert_eq!(Some(50), heap.amount_to_lead("auction_id", "1", 5));
+        assert_eq!(Some(35), heap.amount_to_lead("auction_id", "0", 5));
+        assert_eq!(
+            None,
+            heap.amount_to_lead("auction_id", "missing_bidder_id", 5)
+        );
+    }
+
+    #[test]
+    fn amount_to_lead_works_for_leading_bidder() {
+        let heap = TestData::heap(&[40, 10, 70]);
+
+        assert_eq!(Some(0), heap.amount_to_lead("auction_id", "2", 5));
+        assert_eq!(None, heap.amount_to_lead("missing_auction_id", "2", 5));
+    }
+
+    #[test]
+    fn to_columns_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25]);
+        heap.remove(3);
+
+        let columns = heap.to_columns();
+        assert_eq!(3, columns.len());
+        assert_eq!(columns.len(), columns.auction_ids.len());
+        assert_eq!(columns.len(), columns.bidder_ids.len());
+        assert_eq!(columns.len(), columns.amounts.len());
+        assert_eq!(columns.len(), columns.made_ats.len());
+        assert_eq!(vec![2, 0, 1], columns.ids);
+        assert_eq!(vec![70, 40, 10], columns.amounts);
+        assert_eq!(vec!["2", "0", "1"], columns.bidder_ids);
+        assert_eq!(TestData::bid(0, 40).made_at, columns.made_ats[1]);
+        assert!(BiddingHeap::new().to_columns().is_empty());
+    }
+
+    #[test]
+    fn estimated_win_probability_works() {
+        let mut heap = TestData::heap(&[10, 20, 30, 40, 50]);
+        heap.remove(4);
+
+        assert_eq!(0.0, heap.estimated_win_probability("auction_id", 10));
+        assert_eq!(0.5, heap.estimated_win_probability("auction_id", 25));
+        assert_eq!(0.75, heap.estimated_win_probability("auction_id", 40));
+        assert_eq!(1.0, heap.estimated_win_probability("auction_id", 60));
+        assert_eq!(1.0, heap.estimated_win_probability("missing_auction_id", 0));
+    }
+
+    #[test]
+    fn push_rounded_works() {
+        let mut heap = BiddingHeap::new();
+
+        heap.push_rounded(TestData::bid(0, 1049), 25).unwrap();
+        heap.push_rounded(TestData::bid(1, 1050), 25).unwrap();
+        heap.push_rounded(TestData::bid(2, -1), 25).unwrap();
+        assert_eq!(
+            vec![1050, 1025, -25],
+            heap.drain_sorted()
+                .iter()
+                .map(|bid| bid.amount)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn push_rounded_rejects_invalid_tick() {
+        let mut heap = BiddingHeap::new();
+
+        assert_eq!(
+            Err(BidError::InvalidTick),
+            heap.push_rounded(TestData::bid(0, 1049), 0)
+        );
+        assert_eq!(
+            Err(BidError::InvalidTick),
+            heap.push_rounded(TestData::bid(0, 1049), -25)
+        );
+        assert_eq!(
+            Err(BidError::Overflow),
+            heap.push_rounded(TestData::bid(0, i32::MIN), 3)
+        );
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn would_be_outbid_by_works() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25, 50]);
+        heap.remove(3);
+        let mut other_bid = TestData::bid(5, 5);
+        other_bid.auction_id = String::from("other_auction_id");
+        heap.push(other_bid);
+
+        let ids: Vec<i32> = heap
+            .would_be_outbid_by("auction_id", 50)
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+        assert_eq!(vec![0, 1], ids);
+        assert!(heap.would_be_outbid_by("auction_id", 10).is_empty());
+    }
+
+    #[test]
+    fn diff_since_and_apply_diff_work() {
+        let mut heap = TestData::heap(&[40, 10, 70, 25]);
+        let checkpoint = heap.snapshot();
+
+        heap.push(TestData::bid(4, 55));
+        heap.remove(1);
+        heap.pop();
+        heap.scale_amounts(2).unwrap();
+
+        let diff = heap.diff_since(&checkpoint);
+        assert_eq!(
+            vec![4],
+            diff.added.iter().map(|bid| bid.id).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(String::from("auction_id"), 2)], diff.removed);
+        let mut updated_ids: Vec<i32> = diff.updated.iter().map(|bid| bid.id).collect();
+        updated_ids.sort_unstable();
+        assert_eq!(vec![0, 1, 3], updated_ids);
+
+        let mut restored: BiddingHeap = BiddingHeap::from(checkpoint);
+        restored.apply_diff(&diff);
+        assert!(restored.is_valid());
+        assert!(restored.diff_since(&heap.snapshot()).is_empty());
+        assert_eq!(restored.drain_sorted(), heap.drain_sorted());
+    }
+
+    #[test]
+    fn diff_since_is_empty_without_changes() {
+        let heap = TestData::heap(&[40, 10, 70]);
+
+        assert!(heap.diff_since(&heap.snapshot()).is_empty());
+    }
+
+    #[test]
+    fn visit_level_order_works() {
+        let heap = TestData::heap(&[40, 10, 70, 25, 55]);
+        let mut visits = Vec::new();
+        heap.visit_level_order(|depth, bid| visits.push((depth, bid.amount)));
+
+        assert_eq!((0, 70), visits[0]);
+        assert_eq!(
+            vec![0, 1, 1, 2, 2],
+            visits.iter().map(|(depth, _)| *depth).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            heap.as_slice()
+                .iter()
+                .map(|bid| bid.amount)
+                .collect::<Vec<_>>(),
+            visits.iter().map(|(_, amount)| *amount).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn visit_level_order_works_with_arity() {
+        let heap = TestData::heap_with_arity::<3>(&[40, 10, 70, 25, 55]);
+        let mut depths = Vec::new();
+        heap.visit_level_order(|depth, _| depths.push(depth));
+
+        assert_eq!(vec![0, 1, 1, 1, 2], depths);
+    }
+
+    #[test]
+    fn entry_floor_works_for_empty_auction() {
+        let mut heap = TestData::heap(&[40]);
+        heap.remove(0);
+
+        assert_eq!(100, heap.entry_floor("auction_id", 100, 5));
+        assert_eq!(100, heap.entry_floor("missing_auction_id", 100, 5));
+    }
+
+    #[test]
+    fn entry_floor_works_for_populated_auction() {
+        let heap = TestData::heap(&[40, 10, 70]);
+
+        assert_eq!(75, heap.entry_floor("auction_id", 50, 5));
+        assert_eq!(100, heap.entry_floor("auction_id", 100, 5));
+        assert_eq!(
+            i32::MAX,
+            TestData::heap(&[i32::MAX]).entry_floor("auction_id", 0, 5)
+        );
+    }
+
+    #[test]
+    fn increase_amount_works() {
+        let mut heap = TestData::heap(&[70, 50, 60, 10, 20, 30, 40]);
+        let before: Vec<i32> = heap.as_slice().iter().map(|bid| bid.id).collect();
+
+        // the bid at index 6 moves up through index 2 to the root; only that path changes
+        assert_eq!(Ok(true), heap.increase_amount(6, 100));
+        assert!(heap.is_valid());
+        let after: Vec<i32> = heap.as_slice().iter().map(|bid| bid.id).collect();
+        assert_eq!(
+            vec![6, before[1], before[0], before[3], before[4], before[5], before[2]],
+            after
+        );
+        assert_eq!(140, heap.peek().unwrap().amount);
+
+        assert_eq!(Ok(false), heap.increase_amount(99, 1));
+        assert_eq!(Err(BidError::Overflow), heap.increase_amount(6, u32::MAX));
+        assert!(heap.is_valid());
+    }
+
+    #[test]
+    fn decrease_amount_works() {
+        let mut heap = TestData::heap(&[70, 50, 60, 10, 20, 30, 40]);
+        let before: Vec<i32> = heap.as_slice().iter().map(|bid| bid.id).collect();
+
+        // the root moves down through index 2 to index 6; only that path changes
+        assert_eq!(Ok(true), heap.decrease_amount(0, 65));
+        assert!(heap.is_valid());
+        let after: Vec<i32> = heap.as_slice().iter().map(|bid| bid.id).collect();
+        assert_eq!(
+            vec![before[2], before[1], before[6], before[3], before[4], before[5], 0],
+            after
+        );
+        assert_eq!(60, heap.peek().unwrap().amount);
+
+        heap.remove(1);
+        assert_eq!(Ok(false), heap.decrease_amount(1, 1));
+        assert_eq!(
+            Err(BidError::Overflow),
+            TestData::heap(&[i32::MIN]).decrease_amount(0, 1)
+        );
+    }
+
+    #[test]
+    fn bids_without_auction_works() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        let mut orphan_bid = TestData::bid(3, 25);
+        orphan_bid.auction_id = String::from("deleted_auction_id");
+        heap.push(orphan_bid);
+        heap.remove(1);
+        let known_auctions: HashSet<String> =
+            vec![String::from("auction_id")].into_iter().collect();
+
+        let orphans = heap.bids_without_auction(&known_auctions);
+        assert_eq!(
+            vec![3],
+            orphans.iter().map(|bid| bid.id).collect::<Vec<_>>()
+        );
+        assert_eq!(4, heap.bids_without_auction(&HashSet::new()).len());
+    }
+
+    #[test]
+    fn retract_within_grace_works() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        let now = TestData::bid(2, 70).made_at + Duration::seconds(30);
+
+        assert_eq!(
+            Ok(()),
+            heap.retract_within_grace(2, Duration::seconds(30), now)
+        );
+        assert_eq!(0, heap.peek().unwrap().id);
+        assert_eq!(
+            Some(RemovalReason::Withdrawn),
+            heap.as_slice()
+                .iter()
+                .find(|bid| bid.id == 2)
+                .unwrap()
+                .removal_reason
+        );
+        assert!(heap.tombstones()[0].is_active());
+        assert_eq!(
+            Err(BidError::BidNotFound),
+            heap.retract_within_grace(2, Duration::seconds(30), now)
+        );
+    }
+
+    #[test]
+    fn retract_within_grace_rejects_expired_grace() {
+        let mut heap = TestData::heap(&[40, 10, 70]);
+        let now = TestData::bid(2, 70).made_at + Duration::seconds(31);
+
+        assert_eq!(
+            Err(BidError::GraceExpired),
+            heap.retract_within_grace(2, Duration::seconds(30), now)
+        );
+        assert_eq!(2, heap.peek().unwrap().id);
+        assert!(heap.tombstones().is_empty());
+    }
+
+    #[test]
+    fn leader_history_works() {
+        let removed_at = TestData::made_at() + Duration::seconds(10);
+        let events = vec![
+            BidEvent::Placed(TestData::bid(0, 40)),
+            BidEvent::Placed(TestData::bid(1, 70)),
+            BidEvent::Placed(TestData::bid(2, 50)),
+            BidEvent::Placed(TestData::bid(3, 90)),
+            BidEvent::Removed {
+                id: 3,
+                at: removed_at,
+            },
+            BidEvent::Removed {
+                id: 2,
+                at: removed_at + Duration::seconds(1),
+            },
+        ];
+
+        let timeline: Vec<(DateTime<Utc>, i32)> = BiddingHeap::leader_history(&events)
+            .into_iter()
+            .map(|(at, leader)| (at, leader.id))
+            .collect();
+        assert_eq!(
+            vec![
+                (TestData::bid(0, 40).made_at, 0),
+                (TestData::bid(1, 70).made_at, 1),
+                (TestData::bid(3, 90).made_at, 3),
+                (removed_at, 1),
+            ],
+            timeline
+        );
+        assert!(BiddingHeap::leader_history(&[]).is_empty());
+    }
+
+    #[test]
+    fn leader_diff_works() {
+        let mut other_bid = TestData::bid(7, 90);
+        other_bid.auction_id = "other".to_string();
+        let mut ours = BiddingHeap::from(vec![TestData::bid(0, 40), other_bid.clone()]);
+        let mut theirs: BiddingHeap =
+            BiddingHeap::from(vec![TestData::bid(0, 40), TestData::bid(1, 70), other_bid]);
+
+        let diff = ours.leader_diff(&theirs);
+        assert_eq!(1, diff.len());
+        assert_eq!(
+            Some(&(Some(0), Some(1))),
+            diff.get(&TestData::bid(0, 40).auction_id)
+        );
+
+        theirs.remove(7);
+        ours.push(TestData::bid(1, 70));
+        let diff = ours.leader_diff(&theirs);
+        assert_eq!(1, diff.len());
+        assert_eq!(Some(&(Some(7), None)), diff.get("other"));
+        assert!(ours.leader_diff(&ours).is_empty());
+    }
+
+    #[test]
+    fn with_tie_break_works() {
+        let earlier = TestData::bid(0, 40);
+        let mut later = TestData::bid(1, 40);
+        later.made_at = earlier.made_at + Duration::seconds(1);
+        let pop_order = |tie_break: TieBreak| {
+            let mut heap = BiddingHeap::with_tie_break(tie_break);
+            heap.push(later.clone());
+            heap.push(earlier.clone());
+            heap.push(TestData::bid(2, 10));
+            assert_eq!(tie_break, heap.tie_break());
+            assert!(heap.is_valid());
+            std::iter::from_fn(|| heap.pop())
+                .map(|bid| bid.id)
+                .collect::<Vec<i32>>()
+        };
+
+        assert_eq!(vec![0, 1, 2], pop_order(TieBreak::EarliestWins));
+        assert_eq!(vec![1, 0, 2], pop_order(TieBreak::LatestWins));
+        assert_eq!(TieBreak::EarliestWins, BiddingHeap::new().tie_break());
+    }
+
+    fn spill_path(test: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "bidding_heap_{}_{}.ndjson",
+            test,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn with_spill_works() {
+        let path = spill_path("with_spill_works");
+        let amounts = [30, 90, 10, 60, 60, 20, 80, 40, 70, 50];
+        let mut heap: SpillingHeap = BiddingHeap::with_spill(3, path.clone()).unwrap();
+        let mut expected: BiddingHeap = BiddingHeap::new();
+        for (id, amount) in amounts.iter().enumerate() {
+            let mut bid = TestData::bid(id as i32, *amount);
+            if id == 1 {
+                bid.remove();
+            }
+            heap.push(bid.clone()).unwrap();
+            expected.push(bid);
+        }
+        assert_eq!(10, heap.len());
+        assert_eq!(9, heap.spilled_len());
+        assert_eq!(Some(6), heap.peek().map(|bid| bid.id));
+
+        // pops that drain memory reload the spill, and late low bids still come out in order
+        let mut drained = Vec::new();
+        for _ in 0..4 {
+            drained.push(heap.pop().unwrap().unwrap());
+        }
+        for (id, amount) in [(10, 35), (11, 5)].iter() {
+            heap.push(TestData::bid(*id, *amount)).unwrap();
+            expected.push(TestData::bid(*id, *amount));
+        }
+        drained.extend(heap.drain_sorted().unwrap());
+
+        let drained_ids: Vec<i32> = drained.iter().map(|bid| bid.id).collect();
+        let expected_ids: Vec<i32> = expected.drain_sorted().iter().map(|bid| bid.id).collect();
+        assert_eq!(expected_ids, drained_ids);
+        assert_eq!(vec![6, 8, 3, 4, 9, 7, 10, 0, 5, 2, 11, 1], drained_ids);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn with_spill_truncates_stale_files() {
+        let path = spill_path("with_spill_truncates_stale_files");
+        std::fs::write(
+            &path,
+            format!(
+                "{}\n",
+                TestData::bid(99, 1000).to_json_deterministic().unwrap()
+            ),
+        )
+        .unwrap();
+
+        let mut heap: SpillingHeap = BiddingHeap::with_spill(1, path.clone()).unwrap();
+        for (id, amount) in [20, 10, 30].iter().enumerate() {
+            heap.push(TestData::bid(id as i32, *amount)).unwrap();
+        }
+
+        let drained_ids: Vec<i32> = heap
+            .drain_sorted()
+            .unwrap()
+            .iter()
+            .map(|bid| bid.id)
+            .collect();
+        assert_eq!(vec![2, 0, 1], drained_ids);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn with_spill_bounds_memory() {
+        let path = spill_path("with_spill_bounds_memory");
+        let mut heap: SpillingHeap = BiddingHeap::with_spill(4, path.clone()).unwrap();
+        let mut expected: BiddingHeap = BiddingHeap::new();
+        for id in 0..100 {
+            let bid = TestData::bid(id, id * 37 % 101);
+            heap.push(bid.clone()).unwrap();
+            expected.push(bid);
+            assert!(heap.heap.len() <= 4);
+        }
+        assert_eq!(100, heap.len());
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+
+        // reloads bring back a threshold-sized batch at most, and leave memory within it
+        let mut drained = Vec::new();
+        while let Some(bid) = heap.pop().unwrap() {
+            drained.push(bid.id);
+            assert!(heap.heap.len() <= 4);
+        }
+        let expected_ids: Vec<i32> = expected.drain_sorted().iter().map(|bid| bid.id).collect();
+        assert_eq!(expected_ids, drained);
+        assert_eq!(0, std::fs::metadata(&path).unwrap().len());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn into_heap_works() {
+        let path = spill_path("into_heap_works");
+        let mut heap: SpillingHeap<4> = BiddingHeap::with_spill(0, path.clone()).unwrap();
+        for (id, amount) in [40, 10, 70, 25, 55].iter().enumerate() {
+            heap.push(TestData::bid(id as i32, *amount)).unwrap();
+        }
+        assert_eq!(5, heap.spilled_len());
+        assert_eq!(Some(2), heap.peek().map(|bid| bid.id));
+
+        let heap = heap.into_heap().unwrap();
+        assert_eq!(5, heap.len());
+        assert!(heap.validate_index());
+        let drained_ids: Vec<i32> = heap.drain_sorted().iter().map(|bid| bid.id).collect();
+        assert_eq!(vec![2, 4, 0, 3, 1], drained_ids);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn bid_velocity_works() {
+        let now = TestData::made_at() + Duration::minutes(10);
+        let mut heap = BiddingHeap::new();
+        for (id, seconds_ago) in [30, 60, 90, 120, 150, 170, 600].iter().enumerate() {
+            let mut bid = TestData::bid(id as i32, 10 + id as i32);
+            bid.made_at = now - Duration::seconds(*seconds_ago);
+            heap.push(bid);
+        }
+        let mut removed = TestData::bid(7, 100);
+        removed.made_at = now - Duration::seconds(10);
+        removed.remove();
+        heap.push(removed);
+
+        assert_eq!(
+            2.0,
+            heap.bid_velocity("auction_id", Duration::minutes(3), now)
+        );
+        assert_eq!(
+            1.5,
+            heap.bid_velocity("auction_id", Duration::minutes(2), now)
+        );
+        assert_eq!(
+            1.0,
+            heap.bid_velocity("auction_id", Duration::seconds(60), now)
+        );
+        assert_eq!(
+            0.0,
+            heap.bid_velocity("missing_auction_id", Duration::minutes(2), now)
+        );
+        assert_eq!(0.0, heap.bid_velocity("auction_id", Duration::zero(), now));
+    }
+
+    #[test]
+    fn push_allocated_works() {
+        let mut allocator = BidIdAllocator::new(2).unwrap();
+        let mut heap = BiddingHeap::new();
+        let mut ids_by_bidder: HashMap<&str, Vec<i32>> = HashMap::new();
+        for (bidder_id, amount) in [("a", 10), ("b", 20), ("a", 30), ("a", 40), ("c", 50)].iter() {
+            let id = heap
+                .push_allocated(
+                    String::from("auction_id"),
+                    bidder_id.to_string(),
+                    *amount,
+                    &mut allocator,
+                )
+                .unwrap();
+            ids_by_bidder.entry(bidder_id).or_default().push(id);
+        }
+
+        for (bidder_id, ids) in &ids_by_bidder {
+            let ranges = allocator.ranges(bidder_id);
+            assert!(ids
+                .iter()
+                .all(|id| ranges.iter().any(|range| range.contains(id))));
+        }
+        let ids: HashSet<i32> = heap.as_slice().iter().map(|bid| bid.id).collect();
+        assert_eq!(5, ids.len());
+        assert_eq!(vec![0, 1, 4], ids_by_bidder["a"]);
+        assert_eq!(
+            Some(("c", 50)),
+            heap.peek().map(|bid| (bid.bidder_id.as_str(), bid.amount))
+        );
+    }
+
+    #[test]
+    fn default_works() {
+        let mut heap = BiddingHeap::<3>::default();
+        for (id, amount) in [40, 10, 70, 30, 50].iter().enumerate() {
+            heap.push(TestData::bid(id as i32, *amount));
+        }
+
+        assert!(heap.is_valid());
+        assert_eq!(Some(70), heap.peek().map(|bid| bid.amount));
+        assert!(BiddingHeap::<2>::default().is_empty());
+    }
+
+    #[test]
+    fn cached_comparator_distinguishes_auctions() {
+        let mut comparator = CachedComparator::new(|a: &Bid, b: &Bid| a.cmp_bids(b));
+        let higher_bid = TestData::bid(0, 70);
+        let lower_bid = TestData::bid(1, 40);
+        let mut other_auction_lower_bid = TestData::bid(0, 10);
+        other_auction_lower_bid.auction_id = String::from("other_auction_id");
+        let mut other_auction_higher_bid = TestData::bid(1, 90);
+        other_auction_higher_bid.auction_id = String::from("other_auction_id");
+
+        assert_eq!(
+            Ordering::Greater,
+            comparator.compare(&higher_bid, &lower_bid)
+        );
+        assert_eq!(
+            Ordering::Less,
+            comparator.compare(&other_auction_lower_bid, &other_auction_higher_bid)
+        );
+        assert_eq!(Ordering::Less, comparator.compare(&lower_bid, &higher_bid));
+    }
+
+    #[test]
+    fn snapshot_keeps_tie_break() {
+        let earlier = TestData::bid(0, 40);
+        let mut later = TestData::bid(1, 40);
+        later.made_at = earlier.made_at + Duration::seconds(1);
+        let mut heap = BiddingHeap::<3>::with_arity_and_tie_break(TieBreak::LatestWins);
+        heap.push(earlier);
+        heap.push(later);
+        assert_eq!(Some(1), heap.peek().map(|bid| bid.id));
+
+        let snapshot = heap.snapshot();
+        assert_eq!(TieBreak::LatestWins, snapshot.tie_break);
+        let restored = BiddingHeap::<3>::from(snapshot);
+        assert_eq!(TieBreak::LatestWins, restored.tie_break());
+        assert_eq!(Some(1), restored.peek().map(|bid| bid.id));
+
+        let legacy: HeapSnapshot = serde_json::from_str(r#"{"bids": []}"#).unwrap();
+        assert_eq!(TieBreak::EarliestWins, legacy.tie_break);
+    }
+}
+
+#[cfg(all(test, feature = "bincode"))]
+mod bincode_serialization_and_deserialization {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bidding_heap::BiddingHeap;
+    use crate::models::v1::tie_break::TieBreak;
+
+    #[test]
+    fn it_can_round_trip_through_bincode() {
+        let mut heap = BiddingHeap::new();
+        for (id, amount) in [(0, 40), (1, 10), (2, 70)] {
+            heap.push(Bid::new(
+                String::from("auction_id"),
+                String::from("bidder_id"),
+                id,
+                amount,
+            ));
+        }
+        heap.remove(0);
+
+        let bytes = heap.to_bincode().unwrap();
+        let decoded = BiddingHeap::from_bincode(&bytes).unwrap();
+        assert_eq!(heap.as_slice(), decoded.as_slice());
+        assert!(bytes.len() < serde_json::to_vec(heap.as_slice()).unwrap().len());
+    }
+
+    #[test]
+    fn it_keeps_the_tie_break_through_bincode() {
+        let mut heap = BiddingHeap::with_tie_break(TieBreak::LatestWins);
+        for id in 0..2 {
+            let mut bid = Bid::new(
+                String::from("auction_id"),
+                String::from("bidder_id"),
+                id,
+                40,
+            );
+            bid.made_at += chrono::Duration::seconds(id as i64);
+            heap.push(bid);
+        }
+
+        let decoded = BiddingHeap::from_bincode(&heap.to_bincode().unwrap()).unwrap();
+        assert_eq!(TieBreak::LatestWins, decoded.tie_break());
+        assert_eq!(Some(1), decoded.peek().map(|bid| bid.id));
+    }
+
+    #[test]
+    fn it_keeps_tombstones_through_bincode() {
+        let mut heap = BiddingHeap::new();
+        for id in 0..3 {
+            heap.push(Bid::new(
+                String::from("auction_id"),
+                String::from("bidder_id"),
+                id,
+                40 + id,
+            ));
+        }
+        heap.remove(0);
+        heap.trim_lowest();
+
+        let decoded = BiddingHeap::from_bincode(&heap.to_bincode().unwrap()).unwrap();
+        assert_eq!(2, decoded.tombstones().len());
+        assert_eq!(heap.tombstones(), decoded.tombstones());
+        assert_eq!(heap.as_slice(), decoded.as_slice());
+    }
+}
+
+#[cfg(all(test, feature = "logging"))]
+mod logging {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bidding_heap::BiddingHeap;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use std::sync::Mutex;
+
+    struct TestLogger {
+        records: Mutex<Vec<(Level, String)>>,
+    }
+
+    impl Log for TestLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            let mut records = self.records.lock().unwrap();
+            records.push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: TestLogger = TestLogger {
+        records: Mutex::new(Vec::new()),
+    };
+
+    #[test]
+    fn push_logs_bid_id_and_heap_length() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(LevelFilter::Trace);
+
+        let mut heap = BiddingHeap::new();
+        heap.push(Bid::new(
+            String::from("auction_id"),
+            String::from("bidder_id"),
+            4242,
+            100,
+        ));
+
+        let records = LOGGER.records.lock().unwrap();
+        let expected = (Level::Debug, String::from("pushed bid 4242; heap length 1"));
+        assert!(records.contains(&expected));
+    }
+
+    #[test]
+    fn bulk_mutators_log_what_they_changed() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(LevelFilter::Trace);
+
+        let mut heap = BiddingHeap::new();
+        for id in 4300..4303 {
+            heap.push(Bid::new(
+                String::from("auction_id"),
+                String::from("bidder_id"),
+                id,
+                100,
+            ));
+        }
+        heap.remove_ids(&[4300, 4301]);
+        heap.compact();
+
+        let records = LOGGER.records.lock().unwrap();
+        for message in [
+            "removed 2 matching bids; heap length 3",
+            "compacted 2 removed bids; heap length 1",
+        ] {
+            assert!(records.contains(&(Level::Debug, String::from(message))));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tracing_spans {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bidding_heap::BiddingHeap;
+    use tracing_test::traced_test;
+
+    #[traced_test]
+    #[test]
+    fn push_emits_span() {
+        let mut heap = BiddingHeap::new();
+        heap.push(Bid::new(
+            String::from("traced_auction_id"),
+            String::from("bidder_id"),
+            0,
+            100,
+        ));
+
+        assert!(logs_contain(
+            "bidding_heap.push{auction_id=traced_auction_id"
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn pop_and_remove_emit_spans() {
+        let mut heap = BiddingHeap::new();
+        for id in 0..2 {
+            heap.push(Bid::new(
+                String::from("traced_auction_id"),
+                String::from("bidder_id"),
+                id,
+                100,
+            ));
+        }
+        heap.remove(1);
+        heap.pop();
+
+        assert!(logs_contain(
+            "bidding_heap.remove{bid_id=1 auction_id=traced_auction_id}"
+        ));
+        assert!(logs_contain(
+            "bidding_heap.pop{auction_id=traced_auction_id}"
+        ));
+    }
+
+    #[traced_test]
+    #[test]
+    fn bulk_and_amount_mutators_emit_spans() {
+        let mut heap = BiddingHeap::new();
+        for id in 0..4 {
+            heap.push(Bid::new(
+                String::from("traced_auction_id"),
+                String::from("bidder_id"),
+                id,
+                100 + id,
+            ));
+        }
+        heap.increase_amount(0, 5).unwrap();
+        heap.decrease_amount(3, 5).unwrap();
+        heap.trim_lowest();
+        heap.remove_ids(&[1]);
+        heap.scale_amounts(2).unwrap();
+        heap.collapse_bidder_bids("traced_auction_id");
+        heap.compact();
+
+        assert!(logs_contain(
+            "bidding_heap.increase_amount{bid_id=0 delta=5 auction_id=traced_auction_id}"
+        ));
+        assert!(logs_contain(
+            "bidding_heap.decrease_amount{bid_id=3 delta=5 auction_id=traced_auction_id}"
+        ));
+        assert!(logs_contain(
+            "bidding_heap.trim_lowest{auction_id=traced_auction_id bid_id=3}"
+        ));
+        assert!(logs_contain(
+            "bidding_heap.remove_ids{ids=1}:bidding_heap.remove_where"
+        ));
+        assert!(logs_contain("bidding_heap.scale_amounts{factor=2}"));
+        assert!(logs_contain(
+            "bidding_heap.collapse_bidder_bids{auction_id=traced_auction_id}"
+        ));
+        assert!(logs_contain("bidding_heap.compact"));
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod metrics_counters {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bidding_heap::BiddingHeap;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+    use std::collections::HashMap;
+
+    fn counters(snapshotter: &Snapshotter) -> HashMap<String, u64> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .filter_map(|(key, _, _, value)| match value {
+                DebugValue::Counter(count) => Some((key.key().name().to_string(), count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn push_and_remove_advance_counters() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let mut heap = BiddingHeap::new();
+            for id in 0..3 {
+                heap.push(Bid::new(
+                    String::from("auction_id"),
+                    String::from("bidder_id"),
+                    id,
+                    100 + id,
+                ));
+            }
+            heap.remove(1);
+        });
+
+        let counters = counters(&snapshotter);
+        assert_eq!(Some(&3), counters.get("bids_placed_total"));
+        assert_eq!(Some(&1), counters.get("bids_removed_total"));
+    }
+
+    fn heap_sizes(snapshotter: &Snapshotter) -> Vec<f64> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .find_map(|(key, _, _, value)| match value {
+                DebugValue::Histogram(values) if key.key().name() == "heap_size" => Some(values),
+                _ => None,
+            })
+            .unwrap()
+            .into_iter()
+            .map(|size| size.into_inner())
+            .collect()
+    }
+
+    #[test]
+    fn trim_and_compact_record_heap_size() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let expected = metrics::with_local_recorder(&recorder, || {
+            let mut heap = BiddingHeap::new();
+            for id in 0..3 {
+                heap.push(Bid::new(
+                    String::from("auction_id"),
+                    String::from("bidder_id"),
+                    id,
+                    100 + id,
+                ));
+            }
+            let mut expected = vec![1.0, 2.0, 3.0];
+            heap.trim_lowest();
+            expected.push(heap.len() as f64);
+            heap.compact();
+            expected.push(heap.len() as f64);
+            expected
+        });
+
+        assert_eq!(expected, heap_sizes(&snapshotter));
+        assert_eq!(Some(&2.0), expected.last());
+    }
+
+    #[test]
+    fn bulk_removals_record_heap_size() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let expected = metrics::with_local_recorder(&recorder, || {
+            let mut heap = BiddingHeap::new();
+            for id in 0..4 {
+                heap.push(Bid::new(
+                    String::from("auction_id"),
+                    String::from("bidder_id"),
+                    id,
+                    100 + id,
+                ));
+            }
+            let mut expected = vec![1.0, 2.0, 3.0, 4.0];
+            heap.remove_where(|bid| bid.id == 0);
+            expected.push(heap.len() as f64);
+            heap.collapse_bidder_bids("auction_id");
+            expected.push(heap.len() as f64);
+            expected
+        });
+
+        assert_eq!(expected, heap_sizes(&snapshotter));
+    }
+
+    #[test]
+    fn pushes_record_heap_size() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let mut heap = BiddingHeap::new();
+            for id in 0..2 {
+                heap.push(Bid::new(
+                    String::from("auction_id"),
+                    String::from("bidder_id"),
+                    id,
+                    100,
+                ));
+            }
+            heap.pop();
+        });
+
+        assert_eq!(vec![1.0, 2.0, 1.0], heap_sizes(&snapshotter));
+    }
+}
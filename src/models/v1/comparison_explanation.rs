@@ -0,0 +1,19 @@
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ComparisonRule {
+    // one bid is active and the other removed; the active bid ranks higher regardless of amount
+    ActivityOverride,
+    // amounts differ; difference is the left amount minus the right amount
+    AmountDifference { difference: i64 },
+    // amounts are equal, so the earlier bid ranks higher
+    TimeTieBreak,
+    // equal amounts and times; neither bid ranks higher
+    Equivalent,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ComparisonExplanation {
+    pub ordering: Ordering,
+    pub rule: ComparisonRule,
+}
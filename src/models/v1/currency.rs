@@ -0,0 +1,20 @@
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    Eur,
+    Gbp,
+    Jpy,
+    Kwd,
+    Usd,
+}
+
+impl Currency {
+    // the number of decimal places in the currency's minor unit, per ISO 4217
+    pub fn minor_unit_digits(self) -> u32 {
+        match self {
+            Currency::Jpy => 0,
+            Currency::Eur | Currency::Gbp | Currency::Usd => 2,
+            Currency::Kwd => 3,
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use crate::models::v1::bid::Bid;
+use crate::models::v1::bid_error::BidError;
+use crate::models::v1::currency::Currency;
+use crate::models::v1::removal_reason::RemovalReason;
+use chrono::Utc;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct DecimalBid {
+    pub auction_id: String,
+    pub bidder_id: String,
+    pub id: i32,
+    pub amount: Decimal,
+    pub made_at: chrono::DateTime<chrono::Utc>,
+    pub removed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub removal_reason: Option<RemovalReason>,
+    #[serde(default)]
+    pub currency: Option<Currency>,
+}
+
+impl DecimalBid {
+    // mirrors Bid with a decimal amount
+    // - the integer model counts minor units, so conversions take the number of decimal places
+
+    pub fn new(auction_id: String, bidder_id: String, id: i32, amount: Decimal) -> Self {
+        DecimalBid {
+            id,
+            auction_id,
+            bidder_id,
+            amount,
+            made_at: Utc::now(),
+            removed_at: None,
+            removal_reason: None,
+            currency: None,
+        }
+    }
+
+    pub fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    // trailing zeros are ignored, so 10.50 USD passes while 10.505 USD does not
+    // - a bid without a currency has no minor unit to check against
+    pub fn validate_precision(&self) -> Result<(), BidError> {
+        match self.currency {
+            Some(currency) if self.amount.normalize().scale() > currency.minor_unit_digits() => {
+                Err(BidError::PrecisionLoss)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn from_bid(bid: Bid, decimal_places: u32) -> Result<Self, BidError> {
+        // Decimal holds at most 28 decimal places
+        let amount = Decimal::try_new(i64::from(bid.amount), decimal_places)
+            .map_err(|_| BidError::Overflow)?;
+        Ok(DecimalBid {
+            auction_id: bid.auction_id,
+            bidder_id: bid.bidder_id,
+            id: bid.id,
+            amount,
+            made_at: bid.made_at,
+            removed_at: bid.removed_at,
+            removal_reason: bid.removal_reason,
+            currency: None,
+        })
+    }
+
+    pub fn into_bid(self, decimal_places: u32) -> Result<Bid, BidError> {
+        let minor_units_per_unit = 10_i64
+            .checked_pow(decimal_places)
+            .ok_or(BidError::Overflow)?;
+        let minor_units = self
+            .amount
+            .checked_mul(Decimal::from(minor_units_per_unit))
+            .ok_or(BidError::Overflow)?;
+        if minor_units.fract() != Decimal::ZERO {
+            return Err(BidError::PrecisionLoss);
+        }
+        let amount = minor_units.to_i32().ok_or(BidError::Overflow)?;
+
+        Ok(Bid {
+            auction_id: self.auction_id,
+            bidder_id: self.bidder_id,
+            id: self.id,
+            amount,
+            made_at: self.made_at,
+            removed_at: self.removed_at,
+            removal_reason: self.removal_reason,
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.removed_at.is_none()
+    }
+
+    pub fn remove(&mut self) {
+        self.removed_at = Some(Utc::now());
+    }
+
+    pub fn is_lower_bid_than(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Less
+    }
+
+    pub fn is_equivalent_bid_to(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Equal
+    }
+
+    pub fn is_higher_bid_than(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Greater
+    }
+
+    // the single source of truth for ordering, as in Bid::cmp_bids
+    // - currencies are not looked at here; compare_bids guards against mixing them
+    pub fn cmp_bids(&self, other: &Self) -> Ordering {
+        match (self.is_active(), other.is_active()) {
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            _ => self
+                .amount
+                .cmp(&other.amount)
+                .then_with(|| other.made_at.cmp(&self.made_at)),
+        }
+    }
+}
+
+// raw amounts in different currencies are not comparable, so such pairs are an error
+// - a bid without a currency only compares with other bids without one
+pub fn compare_bids(a: &DecimalBid, b: &DecimalBid) -> Result<Ordering, BidError> {
+    if a.currency != b.currency {
+        return Err(BidError::CurrencyMismatch);
+    }
+    Ok(a.cmp_bids(b))
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bid_error::BidError;
+    use crate::models::v1::currency::Currency;
+    use crate::models::v1::decimal_bid::{compare_bids, DecimalBid};
+    use chrono::{DateTime, Duration, Utc};
+    use rust_decimal::Decimal;
+    use std::cmp::Ordering;
+    use std::str::FromStr;
+
+    struct TestData {}
+
+    impl TestData {
+        fn made_at() -> DateTime<Utc> {
+            DateTime::<Utc>::from_timestamp(1000, 100).unwrap()
+        }
+
+        fn decimal_bid(id: i32, amount: &str) -> DecimalBid {
+            DecimalBid {
+                auction_id: String::from("auction_id"),
+                bidder_id: id.to_string(),
+                id,
+                amount: Decimal::from_str(amount).unwrap(),
+                made_at: TestData::made_at() + Duration::seconds(id as i64),
+                removed_at: None,
+                removal_reason: None,
+                currency: None,
+            }
+        }
+    }
+
+    #[test]
+    fn from_bid_works() {
+        let bid = Bid {
+            auction_id: String::from("auction_id"),
+            bidder_id: String::from("bidder_id"),
+            id: 0,
+            amount: 12345,
+            made_at: TestData::made_at(),
+            removed_at: None,
+            removal_reason: None,
+        };
+
+        let decimal_bid = DecimalBid::from_bid(bid.clone(), 2).unwrap();
+        assert_eq!(Decimal::from_str("123.45").unwrap(), decimal_bid.amount);
+        assert_eq!(TestData::made_at(), decimal_bid.made_at);
+        assert_eq!(Err(BidError::Overflow), DecimalBid::from_bid(bid, 29));
+    }
+
+    #[test]
+    fn into_bid_works() {
+        let decimal_bid = TestData::decimal_bid(0, "123.45");
+
+        let bid = decimal_bid.into_bid(2).unwrap();
+        assert_eq!(12345, bid.amount);
+    }
+
+    #[test]
+    fn into_bid_rejects_lossy_conversions() {
+        let precise_bid = TestData::decimal_bid(0, "123.456");
+        assert_eq!(Err(BidError::PrecisionLoss), precise_bid.into_bid(2));
+
+        let large_bid = TestData::decimal_bid(1, "99999999999");
+        assert_eq!(Err(BidError::Overflow), large_bid.into_bid(2));
+    }
+
+    #[test]
+    fn comparisons_match_bid() {
+        let lower_bid = TestData::decimal_bid(0, "10.01");
+        let higher_bid = TestData::decimal_bid(1, "10.10");
+        let later_bid = TestData::decimal_bid(2, "10.10");
+
+        assert!(lower_bid.is_lower_bid_than(&higher_bid));
+        assert!(higher_bid.is_higher_bid_than(&lower_bid));
+        assert!(later_bid.is_lower_bid_than(&higher_bid));
+        assert!(higher_bid.is_equivalent_bid_to(&higher_bid));
+
+        let mut removed_bid = TestData::decimal_bid(3, "100.00");
+        removed_bid.remove();
+        assert!(removed_bid.is_lower_bid_than(&lower_bid));
+    }
+
+    #[test]
+    fn validate_precision_accepts_minor_units() {
+        let bid = TestData::decimal_bid(0, "123.45").with_currency(Currency::Usd);
+        assert_eq!(Ok(()), bid.validate_precision());
+
+        let padded_bid = TestData::decimal_bid(1, "123.4500").with_currency(Currency::Usd);
+        assert_eq!(Ok(()), padded_bid.validate_precision());
+
+        let unchecked_bid = TestData::decimal_bid(2, "123.456");
+        assert_eq!(Ok(()), unchecked_bid.validate_precision());
+    }
+
+    #[test]
+    fn validate_precision_rejects_sub_minor_units() {
+        let bid = TestData::decimal_bid(0, "123.456").with_currency(Currency::Usd);
+        assert_eq!(Err(BidError::PrecisionLoss), bid.validate_precision());
+
+        let yen_bid = TestData::decimal_bid(1, "100.5").with_currency(Currency::Jpy);
+        assert_eq!(Err(BidError::PrecisionLoss), yen_bid.validate_precision());
+        let dinar_bid = TestData::decimal_bid(2, "100.505").with_currency(Currency::Kwd);
+        assert_eq!(Ok(()), dinar_bid.validate_precision());
+    }
+
+    #[test]
+    fn cmp_bids_works() {
+        let lower_bid = TestData::decimal_bid(0, "10.01");
+        let higher_bid = TestData::decimal_bid(1, "10.10");
+        let mut later_bid = TestData::decimal_bid(2, "10.01");
+        let mut removed_bid = TestData::decimal_bid(3, "99.99");
+        removed_bid.remove();
+
+        assert_eq!(Ordering::Less, lower_bid.cmp_bids(&higher_bid));
+        assert_eq!(Ordering::Greater, lower_bid.cmp_bids(&later_bid));
+        assert_eq!(Ordering::Greater, lower_bid.cmp_bids(&removed_bid));
+        assert_eq!(
+            Ordering::Equal,
+            lower_bid.cmp_bids(&TestData::decimal_bid(0, "10.01"))
+        );
+        assert!(higher_bid.is_higher_bid_than(&lower_bid));
+        assert!(later_bid.is_lower_bid_than(&lower_bid));
+        later_bid.made_at = lower_bid.made_at;
+        assert!(later_bid.is_equivalent_bid_to(&lower_bid));
+    }
+
+    #[test]
+    fn compare_bids_works_within_currency() {
+        let lower_bid = TestData::decimal_bid(0, "10.01").with_currency(Currency::Usd);
+        let higher_bid = TestData::decimal_bid(1, "10.10").with_currency(Currency::Usd);
+
+        assert_eq!(Ok(Ordering::Less), compare_bids(&lower_bid, &higher_bid));
+        assert_eq!(Ok(Ordering::Greater), compare_bids(&higher_bid, &lower_bid));
+        assert_eq!(Ok(Ordering::Equal), compare_bids(&lower_bid, &lower_bid));
+        assert_eq!(
+            Ok(Ordering::Less),
+            compare_bids(
+                &TestData::decimal_bid(2, "1"),
+                &TestData::decimal_bid(3, "2")
+            )
+        );
+    }
+
+    #[test]
+    fn compare_bids_rejects_cross_currency_pairs() {
+        let dollar_bid = TestData::decimal_bid(0, "10.00").with_currency(Currency::Usd);
+        let euro_bid = TestData::decimal_bid(1, "10.00").with_currency(Currency::Eur);
+
+        assert_eq!(
+            Err(BidError::CurrencyMismatch),
+            compare_bids(&dollar_bid, &euro_bid)
+        );
+        assert_eq!(
+            Err(BidError::CurrencyMismatch),
+            compare_bids(&dollar_bid, &TestData::decimal_bid(2, "10.00"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod serialization_and_deserialization {
+    use crate::models::v1::decimal_bid::DecimalBid;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn it_can_serialize_and_deserialize() {
+        let auction_id: String = String::from("auction_id");
+        let bidder_id: String = String::from("bidder_id");
+        let id: i32 = 0;
+        let amount: Decimal = Decimal::from_str("1234.5678").unwrap();
+        let bid = DecimalBid::new(auction_id, bidder_id, id, amount);
+
+        let result_of_serialization = serde_json::to_string(&bid);
+        assert!(result_of_serialization.is_ok());
+
+        let data = result_of_serialization.unwrap();
+        let result_of_deserialization = serde_json::from_str::<DecimalBid>(&data);
+        assert!(result_of_deserialization.is_ok());
+
+        let deserialized_bid = result_of_deserialization.unwrap();
+        assert_eq!(bid, deserialized_bid);
+        assert_eq!("1234.5678", deserialized_bid.amount.to_string());
+        assert_eq!(4, deserialized_bid.amount.scale());
+    }
+}
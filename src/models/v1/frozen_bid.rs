@@ -0,0 +1,76 @@
+use crate::models::v1::bid::Bid;
+use crate::models::v1::bid_error::BidError;
+use std::ops::Deref;
+
+#[derive(Debug, PartialEq)]
+pub struct FrozenBid {
+    bid: Bid,
+}
+
+impl FrozenBid {
+    // a finalized bid; it reads like a Bid but refuses the mutations a Bid allows
+
+    pub fn new(bid: Bid) -> Self {
+        FrozenBid { bid }
+    }
+
+    pub fn into_inner(self) -> Bid {
+        self.bid
+    }
+
+    pub fn update_amount(&mut self, _amount: i32) -> Result<(), BidError> {
+        Err(BidError::BidFrozen)
+    }
+
+    pub fn remove(&mut self) -> Result<(), BidError> {
+        Err(BidError::BidFrozen)
+    }
+}
+
+impl Deref for FrozenBid {
+    type Target = Bid;
+
+    fn deref(&self) -> &Bid {
+        &self.bid
+    }
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bid_error::BidError;
+
+    fn bid() -> Bid {
+        Bid::new(
+            String::from("auction_id"),
+            String::from("bidder_id"),
+            0,
+            10000,
+        )
+    }
+
+    #[test]
+    fn freeze_works() {
+        let frozen_bid = bid().freeze();
+
+        assert_eq!(10000, frozen_bid.amount);
+        assert!(frozen_bid.is_active());
+        assert_eq!(10000, frozen_bid.into_inner().amount);
+    }
+
+    #[test]
+    fn update_amount_on_frozen_bid_fails() {
+        let mut frozen_bid = bid().freeze();
+
+        assert_eq!(Err(BidError::BidFrozen), frozen_bid.update_amount(1));
+        assert_eq!(10000, frozen_bid.amount);
+    }
+
+    #[test]
+    fn remove_on_frozen_bid_fails() {
+        let mut frozen_bid = bid().freeze();
+
+        assert_eq!(Err(BidError::BidFrozen), frozen_bid.remove());
+        assert!(frozen_bid.is_active());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::models::v1::bid::Bid;
+use crate::models::v1::tie_break::TieBreak;
+
+// a copy of every bid in a heap, active and removed, taken as a checkpoint for later diffs
+// - the tie break is kept so that a restored heap orders equal bids the same way
+// - tombstones are kept so that a restored heap reports the same removal history
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct HeapSnapshot {
+    pub bids: Vec<Bid>,
+    #[serde(default)]
+    pub tombstones: Vec<Bid>,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+}
+
+// the changes between a snapshot and a later heap, with bids identified by (auction_id, id)
+// - added: bids the snapshot did not have
+// - removed: identities of bids that have left the heap entirely, e.g. popped
+// - updated: bids whose contents changed, including soft removals, in their new state
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct HeapDiff {
+    pub added: Vec<Bid>,
+    pub removed: Vec<(String, i32)>,
+    pub updated: Vec<Bid>,
+}
+
+impl HeapDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
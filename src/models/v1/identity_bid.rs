@@ -0,0 +1,81 @@
+use crate::models::v1::bid::Bid;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+#[derive(Debug)]
+pub struct IdentityBid(pub Bid);
+
+// equality and hashing use the stable identity, auction_id and id, and ignore amount and times
+// - Bid's own PartialEq compares every field, so it is not implemented on Bid directly
+
+impl PartialEq for IdentityBid {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.auction_id == other.0.auction_id && self.0.id == other.0.id
+    }
+}
+
+impl Eq for IdentityBid {}
+
+impl Hash for IdentityBid {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.auction_id.hash(state);
+        self.0.id.hash(state);
+    }
+}
+
+impl Deref for IdentityBid {
+    type Target = Bid;
+
+    fn deref(&self) -> &Bid {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::identity_bid::IdentityBid;
+    use std::collections::HashSet;
+
+    fn bid(auction_id: &str, id: i32, amount: i32) -> Bid {
+        Bid::new(
+            String::from(auction_id),
+            String::from("bidder_id"),
+            id,
+            amount,
+        )
+    }
+
+    #[test]
+    fn eq_works() {
+        assert_eq!(
+            IdentityBid(bid("auction_id", 0, 100)),
+            IdentityBid(bid("auction_id", 0, 200))
+        );
+        assert_ne!(
+            IdentityBid(bid("auction_id", 0, 100)),
+            IdentityBid(bid("auction_id", 1, 100))
+        );
+        assert_ne!(
+            IdentityBid(bid("auction_id", 0, 100)),
+            IdentityBid(bid("other_auction_id", 0, 100))
+        );
+    }
+
+    #[test]
+    fn hash_set_dedupes_by_identity() {
+        let mut bids: HashSet<IdentityBid> = HashSet::new();
+
+        assert!(bids.insert(IdentityBid(bid("auction_id", 0, 100))));
+        assert!(!bids.insert(IdentityBid(bid("auction_id", 0, 200))));
+        assert!(bids.insert(IdentityBid(bid("auction_id", 1, 100))));
+        assert!(bids.insert(IdentityBid(bid("other_auction_id", 0, 100))));
+
+        assert_eq!(3, bids.len());
+        let kept = bids
+            .iter()
+            .find(|bid| bid.auction_id == "auction_id" && bid.id == 0)
+            .unwrap();
+        assert_eq!(100, kept.amount);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::models::v1::bid::Bid;
+use crate::models::v1::bid_error::BidError;
+use crate::models::v1::bidding_heap::BiddingHeap;
+use crate::models::v1::removal_reason::RemovalReason;
+use crate::models::v1::tie_break::TieBreak;
+use chrono::{DateTime, Utc};
+use std::convert::TryFrom;
+
+// hand-written prost messages, so no protoc or build script is needed; the matching schema is
+//
+//     message BidProto {
+//         string auction_id = 1;
+//         string bidder_id = 2;
+//         int32 id = 3;
+//         int32 amount = 4;
+//         google.protobuf.Timestamp made_at = 5;
+//         google.protobuf.Timestamp removed_at = 6;
+//         optional RemovalReasonProto removal_reason = 7;
+//     }
+//
+//     message HeapProto {
+//         repeated BidProto bids = 1;
+//         repeated BidProto tombstones = 2;
+//         TieBreakProto tie_break = 3;
+//     }
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BidProto {
+    #[prost(string, tag = "1")]
+    pub auction_id: String,
+    #[prost(string, tag = "2")]
+    pub bidder_id: String,
+    #[prost(int32, tag = "3")]
+    pub id: i32,
+    #[prost(int32, tag = "4")]
+    pub amount: i32,
+    #[prost(message, optional, tag = "5")]
+    pub made_at: Option<prost_types::Timestamp>,
+    #[prost(message, optional, tag = "6")]
+    pub removed_at: Option<prost_types::Timestamp>,
+    #[prost(enumeration = "RemovalReasonProto", optional, tag = "7")]
+    pub removal_reason: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct HeapProto {
+    #[prost(message, repeated, tag = "1")]
+    pub bids: Vec<BidProto>,
+    #[prost(message, repeated, tag = "2")]
+    pub tombstones: Vec<BidProto>,
+    #[prost(enumeration = "TieBreakProto", tag = "3")]
+    pub tie_break: i32,
+}
+
+// EarliestWins is zero, so messages written before tie_break existed decode to the old rule
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, prost::Enumeration)]
+#[repr(i32)]
+pub enum TieBreakProto {
+    EarliestWins = 0,
+    LatestWins = 1,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, prost::Enumeration)]
+#[repr(i32)]
+pub enum RemovalReasonProto {
+    Unspecified = 0,
+    Withdrawn = 1,
+    Expired = 2,
+    Disqualified = 3,
+    Outbid = 4,
+}
+
+fn to_timestamp(date_time: &DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: date_time.timestamp(),
+        nanos: date_time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn from_timestamp(timestamp: &prost_types::Timestamp) -> Result<DateTime<Utc>, BidError> {
+    u32::try_from(timestamp.nanos)
+        .ok()
+        .and_then(|nanos| DateTime::from_timestamp(timestamp.seconds, nanos))
+        .ok_or_else(|| BidError::InvalidProto(format!("timestamp {:?} is out of range", timestamp)))
+}
+
+impl From<&Bid> for BidProto {
+    fn from(bid: &Bid) -> Self {
+        BidProto {
+            auction_id: bid.auction_id.clone(),
+            bidder_id: bid.bidder_id.clone(),
+            id: bid.id,
+            amount: bid.amount,
+            made_at: Some(to_timestamp(&bid.made_at)),
+            removed_at: bid.removed_at.as_ref().map(to_timestamp),
+            removal_reason: bid.removal_reason.map(|reason| {
+                let reason = match reason {
+                    RemovalReason::Withdrawn => RemovalReasonProto::Withdrawn,
+                    RemovalReason::Expired => RemovalReasonProto::Expired,
+                    RemovalReason::Disqualified => RemovalReasonProto::Disqualified,
+                    RemovalReason::Outbid => RemovalReasonProto::Outbid,
+                };
+                reason as i32
+            }),
+        }
+    }
+}
+
+impl TryFrom<BidProto> for Bid {
+    type Error = BidError;
+
+    fn try_from(proto: BidProto) -> Result<Self, BidError> {
+        let made_at = proto
+            .made_at
+            .as_ref()
+            .ok_or_else(|| BidError::InvalidProto(String::from("made_at is missing")))
+            .and_then(from_timestamp)?;
+        let removed_at = proto.removed_at.as_ref().map(from_timestamp).transpose()?;
+        let removal_reason = match proto.removal_reason.map(RemovalReasonProto::try_from) {
+            None | Some(Ok(RemovalReasonProto::Unspecified)) => None,
+            Some(Ok(RemovalReasonProto::Withdrawn)) => Some(RemovalReason::Withdrawn),
+            Some(Ok(RemovalReasonProto::Expired)) => Some(RemovalReason::Expired),
+            Some(Ok(RemovalReasonProto::Disqualified)) => Some(RemovalReason::Disqualified),
+            Some(Ok(RemovalReasonProto::Outbid)) => Some(RemovalReason::Outbid),
+            Some(Err(e)) => return Err(BidError::InvalidProto(e.to_string())),
+        };
+
+        Ok(Bid {
+            auction_id: proto.auction_id,
+            bidder_id: proto.bidder_id,
+            id: proto.id,
+            amount: proto.amount,
+            made_at,
+            removed_at,
+            removal_reason,
+        })
+    }
+}
+
+impl<const D: usize> From<&BiddingHeap<D>> for HeapProto {
+    fn from(heap: &BiddingHeap<D>) -> Self {
+        HeapProto {
+            bids: heap.as_slice().iter().map(BidProto::from).collect(),
+            tombstones: heap.tombstones().iter().map(BidProto::from).collect(),
+            tie_break: match heap.tie_break() {
+                TieBreak::EarliestWins => TieBreakProto::EarliestWins,
+                TieBreak::LatestWins => TieBreakProto::LatestWins,
+            } as i32,
+        }
+    }
+}
+
+impl<const D: usize> TryFrom<HeapProto> for BiddingHeap<D> {
+    type Error = BidError;
+
+    // bids are re-heapified, so a message from another encoder cannot break the heap property
+    fn try_from(proto: HeapProto) -> Result<Self, BidError> {
+        let bids = proto
+            .bids
+            .into_iter()
+            .map(Bid::try_from)
+            .collect::<Result<Vec<Bid>, BidError>>()?;
+        let tombstones = proto
+            .tombstones
+            .into_iter()
+            .map(Bid::try_from)
+            .collect::<Result<Vec<Bid>, BidError>>()?;
+        let tie_break = match TieBreakProto::try_from(proto.tie_break) {
+            Ok(TieBreakProto::EarliestWins) => TieBreak::EarliestWins,
+            Ok(TieBreakProto::LatestWins) => TieBreak::LatestWins,
+            Err(e) => return Err(BidError::InvalidProto(e.to_string())),
+        };
+        Ok(BiddingHeap::from_parts(bids, tombstones, tie_break))
+    }
+}
+
+#[cfg(test)]
+mod serialization_and_deserialization {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::bid_error::BidError;
+    use crate::models::v1::bidding_heap::BiddingHeap;
+    use crate::models::v1::proto::{BidProto, HeapProto};
+    use crate::models::v1::removal_reason::RemovalReason;
+    use crate::models::v1::tie_break::TieBreak;
+    use chrono::{DateTime, Duration};
+    use prost::Message;
+    use std::convert::TryFrom;
+
+    fn bid(id: i32, amount: i32) -> Bid {
+        let mut bid = Bid::new(String::from("auction_id"), id.to_string(), id, amount);
+        bid.made_at = DateTime::from_timestamp(1000, 100).unwrap() + Duration::seconds(id as i64);
+        bid
+    }
+
+    #[test]
+    fn bid_round_trip_works() {
+        let mut removed_bid = bid(0, 40);
+        removed_bid.remove_with_reason(RemovalReason::Expired);
+
+        for bid in [bid(1, 70), removed_bid] {
+            let bytes = BidProto::from(&bid).encode_to_vec();
+            let decoded = BidProto::decode(bytes.as_slice()).unwrap();
+            assert_eq!(Ok(bid), Bid::try_from(decoded));
+        }
+    }
+
+    #[test]
+    fn heap_round_trip_keeps_tie_break() {
+        let mut heap = BiddingHeap::with_tie_break(TieBreak::LatestWins);
+        heap.push(bid(0, 40));
+        heap.push(bid(1, 40));
+
+        let bytes = HeapProto::from(&heap).encode_to_vec();
+        let decoded: BiddingHeap =
+            BiddingHeap::try_from(HeapProto::decode(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(TieBreak::LatestWins, decoded.tie_break());
+        assert_eq!(Some(1), decoded.peek().map(|bid| bid.id));
+
+        let mut proto = HeapProto::from(&heap);
+        proto.tie_break = 7;
+        assert!(matches!(
+            BiddingHeap::<2>::try_from(proto),
+            Err(BidError::InvalidProto(_))
+        ));
+    }
+
+    #[test]
+    fn heap_round_trip_works() {
+        let mut heap = BiddingHeap::new();
+        for (id, amount) in [(0, 40), (1, 10), (2, 70), (3, 25)] {
+            heap.push(bid(id, amount));
+        }
+        heap.remove_with_reason(2, RemovalReason::Withdrawn);
+
+        let bytes = HeapProto::from(&heap).encode_to_vec();
+        let decoded: BiddingHeap =
+            BiddingHeap::try_from(HeapProto::decode(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(heap.as_slice(), decoded.as_slice());
+        assert_eq!(heap.tombstones(), decoded.tombstones());
+    }
+
+    #[test]
+    fn missing_made_at_is_rejected() {
+        let mut proto = BidProto::from(&bid(0, 40));
+        proto.made_at = None;
+
+        assert!(matches!(
+            Bid::try_from(proto),
+            Err(BidError::InvalidProto(_))
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RemovalReason {
+    Withdrawn,
+    Expired,
+    Disqualified,
+    Outbid,
+}
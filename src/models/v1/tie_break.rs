@@ -0,0 +1,73 @@
+use crate::models::v1::bid::Bid;
+use std::cmp::Ordering;
+
+// how bids that are equal on activity and amount are ordered
+// - EarliestWins is the rule Bid::cmp_bids applies; LatestWins reverses only the time tie break
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TieBreak {
+    #[default]
+    EarliestWins,
+    LatestWins,
+}
+
+impl TieBreak {
+    pub fn cmp_bids(self, bid: &Bid, other: &Bid) -> Ordering {
+        match self {
+            TieBreak::EarliestWins => bid.cmp_bids(other),
+            TieBreak::LatestWins => match (bid.is_active(), other.is_active()) {
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                _ => bid
+                    .amount
+                    .cmp(&other.amount)
+                    .then_with(|| bid.made_at.cmp(&other.made_at)),
+            },
+        }
+    }
+
+    pub fn is_higher_bid_than(self, bid: &Bid, other: &Bid) -> bool {
+        self.cmp_bids(bid, other) == Ordering::Greater
+    }
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1::bid::Bid;
+    use crate::models::v1::tie_break::TieBreak;
+    use chrono::Duration;
+    use std::cmp::Ordering;
+
+    fn bids() -> (Bid, Bid) {
+        let earlier = Bid::new(String::from("auction_id"), String::from("0"), 0, 10);
+        let mut later = earlier.clone();
+        later.id = 1;
+        later.made_at = earlier.made_at + Duration::seconds(1);
+        (earlier, later)
+    }
+
+    #[test]
+    fn cmp_bids_works() {
+        let (earlier, later) = bids();
+
+        assert_eq!(
+            Ordering::Greater,
+            TieBreak::EarliestWins.cmp_bids(&earlier, &later)
+        );
+        assert_eq!(
+            Ordering::Less,
+            TieBreak::LatestWins.cmp_bids(&earlier, &later)
+        );
+        assert_eq!(
+            earlier.cmp_bids(&later),
+            TieBreak::default().cmp_bids(&earlier, &later)
+        );
+
+        let mut higher = earlier.clone();
+        higher.amount += 1;
+        assert!(TieBreak::LatestWins.is_higher_bid_than(&higher, &later));
+
+        let mut removed = later.clone();
+        removed.remove();
+        assert!(TieBreak::LatestWins.is_higher_bid_than(&earlier, &removed));
+    }
+}
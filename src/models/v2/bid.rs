@@ -0,0 +1,183 @@
+use crate::models::v1;
+use chrono::Utc;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Bid {
+    pub auction_id: String,
+    pub bidder_id: String,
+    pub id: i32,
+    pub amount: i32,
+    pub tier: i32,
+    pub made_at: chrono::DateTime<chrono::Utc>,
+    pub removed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub removal_reason: Option<v1::removal_reason::RemovalReason>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl Bid {
+    // v1's ordering with a tier in front of the amount
+    // - removed bids still rank below active ones whatever their tier
+    // - a higher tier outranks any amount in a lower tier; within a tier, v1's amount then time
+
+    pub fn new(auction_id: String, bidder_id: String, id: i32, amount: i32, tier: i32) -> Self {
+        Bid {
+            auction_id,
+            bidder_id,
+            id,
+            amount,
+            tier,
+            made_at: Utc::now(),
+            removed_at: None,
+            removal_reason: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    // extra attributes such as the source or user agent; they take no part in ordering
+    pub fn with_metadata(mut self, key: String, value: String) -> Self {
+        self.metadata.insert(key, value);
+        self
+    }
+
+    pub fn from_v1(bid: v1::bid::Bid, tier: i32) -> Self {
+        Bid {
+            auction_id: bid.auction_id,
+            bidder_id: bid.bidder_id,
+            id: bid.id,
+            amount: bid.amount,
+            tier,
+            made_at: bid.made_at,
+            removed_at: bid.removed_at,
+            removal_reason: bid.removal_reason,
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.removed_at.is_none()
+    }
+
+    pub fn remove(&mut self) {
+        self.removed_at = Some(Utc::now());
+    }
+
+    pub fn is_lower_bid_than(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Less
+    }
+
+    pub fn is_equivalent_bid_to(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Equal
+    }
+
+    pub fn is_higher_bid_than(&self, other: &Self) -> bool {
+        self.cmp_bids(other) == Ordering::Greater
+    }
+
+    pub fn cmp_bids(&self, other: &Self) -> Ordering {
+        match (self.is_active(), other.is_active()) {
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            _ => self
+                .tier
+                .cmp(&other.tier)
+                .then_with(|| self.amount.cmp(&other.amount))
+                .then_with(|| other.made_at.cmp(&self.made_at)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod methods {
+    use crate::models::v1;
+    use crate::models::v2::bid::Bid;
+    use chrono::{DateTime, Duration, Utc};
+    use std::cmp::Ordering;
+
+    fn bid(id: i32, amount: i32, tier: i32) -> Bid {
+        let mut bid = Bid::new(String::from("auction_id"), id.to_string(), id, amount, tier);
+        bid.made_at =
+            DateTime::<Utc>::from_timestamp(1000, 100).unwrap() + Duration::seconds(id as i64);
+        bid
+    }
+
+    #[test]
+    fn higher_tier_beats_higher_amount() {
+        let priority_bid = bid(0, 100, 2);
+        let standard_bid = bid(1, 10000, 1);
+
+        assert!(priority_bid.is_higher_bid_than(&standard_bid));
+        assert!(standard_bid.is_lower_bid_than(&priority_bid));
+        assert!(!priority_bid.is_equivalent_bid_to(&standard_bid));
+    }
+
+    #[test]
+    fn amount_then_time_decide_within_tier() {
+        assert_eq!(Ordering::Greater, bid(0, 200, 1).cmp_bids(&bid(1, 100, 1)));
+        assert_eq!(Ordering::Greater, bid(0, 100, 1).cmp_bids(&bid(1, 100, 1)));
+        assert_eq!(Ordering::Equal, bid(0, 100, 1).cmp_bids(&bid(0, 100, 1)));
+    }
+
+    #[test]
+    fn removed_bids_rank_below_any_tier() {
+        let mut removed_bid = bid(0, 100, 5);
+        removed_bid.remove();
+
+        assert!(removed_bid.is_lower_bid_than(&bid(1, 1, 0)));
+    }
+
+    #[test]
+    fn from_v1_works() {
+        let v1_bid = v1::bid::Bid::new(
+            String::from("auction_id"),
+            String::from("bidder_id"),
+            0,
+            100,
+        );
+        let bid = Bid::from_v1(v1_bid.clone(), 3);
+
+        assert_eq!(v1_bid.amount, bid.amount);
+        assert_eq!(v1_bid.made_at, bid.made_at);
+        assert_eq!(3, bid.tier);
+    }
+}
+
+#[cfg(test)]
+mod serialization_and_deserialization {
+    use crate::models::v2::bid::Bid;
+
+    fn bid() -> Bid {
+        Bid::new(
+            String::from("auction_id"),
+            String::from("bidder_id"),
+            0,
+            100,
+            1,
+        )
+    }
+
+    #[test]
+    fn metadata_survives_round_trip() {
+        let bid = bid()
+            .with_metadata(String::from("source"), String::from("mobile"))
+            .with_metadata(String::from("ip"), String::from("203.0.113.7"));
+
+        let data = serde_json::to_string(&bid).unwrap();
+        let deserialized_bid = serde_json::from_str::<Bid>(&data).unwrap();
+        assert_eq!(bid, deserialized_bid);
+        assert_eq!(2, deserialized_bid.metadata.len());
+        assert_eq!("mobile", deserialized_bid.metadata["source"]);
+    }
+
+    #[test]
+    fn metadata_defaults_to_empty() {
+        let mut value = serde_json::to_value(bid()).unwrap();
+        value.as_object_mut().unwrap().remove("metadata");
+
+        let deserialized_bid = serde_json::from_value::<Bid>(value).unwrap();
+        assert!(deserialized_bid.metadata.is_empty());
+    }
+}